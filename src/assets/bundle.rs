@@ -0,0 +1,103 @@
+use enum_map::{enum_map, EnumMap};
+use serde::{Deserialize, Serialize};
+
+use crate::config::TableId;
+
+use super::{intro, table};
+
+pub const BUNDLE_FILE: &str = "PFR.BUN";
+
+const MAGIC: &[u8; 4] = b"PFRB";
+// Bump this whenever any of the serialized asset types changes shape.
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bundle {
+    pub intro: intro::Assets,
+    pub tables: EnumMap<TableId, table::Assets>,
+}
+
+impl Bundle {
+    pub fn extract(intro_prg: &[u8], table_prgs: &EnumMap<TableId, Vec<u8>>) -> Self {
+        Bundle {
+            intro: intro::Assets::load(intro_prg),
+            tables: enum_map! {
+                table => table::Assets::load(&table_prgs[table], table),
+            },
+        }
+    }
+
+    /// Returns `None` if the data is not a bundle, or was written by an incompatible version.
+    pub fn load(data: &[u8]) -> Option<Self> {
+        if data.len() < 8 || &data[..4] != MAGIC {
+            return None;
+        }
+        if u32::from_le_bytes(data[4..8].try_into().unwrap()) != VERSION {
+            return None;
+        }
+        bincode::deserialize(&data[8..]).ok()
+    }
+
+    pub fn save(&self) -> Vec<u8> {
+        let mut res = MAGIC.to_vec();
+        res.extend(VERSION.to_le_bytes());
+        bincode::serialize_into(&mut res, self).unwrap();
+        res
+    }
+}
+
+/// serde only knows about arrays up to 32 elements.
+pub(crate) mod serde_array {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, T: Serialize, const N: usize>(
+        arr: &[T; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(arr)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[T; N], D::Error> {
+        let v = Vec::<T>::deserialize(deserializer)?;
+        let len = v.len();
+        v.try_into()
+            .map_err(|_| D::Error::invalid_length(len, &"an array of the right size"))
+    }
+}
+
+pub(crate) mod serde_tower {
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    type Tower = Box<[[bool; 160]; 167]>;
+
+    pub fn serialize<S: Serializer>(
+        tower: &Option<Tower>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        tower
+            .as_ref()
+            .map(|tower| tower.iter().map(|row| row.to_vec()).collect::<Vec<_>>())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Tower>, D::Error> {
+        let Some(rows) = Option::<Vec<Vec<bool>>>::deserialize(deserializer)? else {
+            return Ok(None);
+        };
+        if rows.len() != 167 {
+            return Err(D::Error::invalid_length(rows.len(), &"167 tower rows"));
+        }
+        let mut res = Box::new([[false; 160]; 167]);
+        for (dst, src) in res.iter_mut().zip(rows) {
+            if src.len() != 160 {
+                return Err(D::Error::invalid_length(src.len(), &"160 tower columns"));
+            }
+            dst.copy_from_slice(&src);
+        }
+        Ok(Some(res))
+    }
+}
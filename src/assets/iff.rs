@@ -1,7 +1,8 @@
 use arrayref::array_ref;
 use ndarray::Array2;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Image {
     pub data: Array2<u8>,
     pub cmap: Vec<(u8, u8, u8)>,
@@ -2,6 +2,7 @@ use crate::assets::mz::MzExe;
 
 use super::iff::Image;
 use ndarray::{concatenate, prelude::*};
+use serde::{Deserialize, Serialize};
 use unnamed_entity::{entity_id, EntityVec};
 
 entity_id! {
@@ -9,7 +10,7 @@ entity_id! {
     pub id TextPageId u8, delta;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Assets {
     // Slideshow
     pub slides: EntityVec<SlideId, Slide>,
@@ -32,7 +33,7 @@ pub struct Assets {
     pub warp_frames: u8,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Slide {
     pub image: Image,
     pub gap_frames: u8,
@@ -42,13 +43,13 @@ pub struct Slide {
     pub fade_from_white: bool,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum TableSet {
     Table12,
     Table34,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TextPage {
     HiScores(TableSet),
     Text(Vec<Vec<u8>>),
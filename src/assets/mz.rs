@@ -1,8 +1,9 @@
 use arrayref::array_ref;
+use serde::{Deserialize, Serialize};
 
 use crate::bcd::Bcd;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MzExe {
     pub image: Vec<u8>,
     pub relocs: Vec<FarPtr>,
@@ -13,7 +14,7 @@ pub struct MzExe {
     pub ds: u16,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct FarPtr {
     pub seg: u16,
     pub off: u16,
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use arrayvec::ArrayVec;
 use enum_map::EnumMap;
 use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
 use unnamed_entity::EntityVec;
 
 use crate::{
@@ -26,6 +27,7 @@ use crate::{
 use sound::{Jingle, JingleBind, Sfx, SfxBind};
 
 use self::{
+    asm::{cheat_label, AsmError},
     dm::DmPalette,
     flippers::{Flipper, FlipperId},
     lights::{AttractLight, AttractLightId, Light, LightBind, LightId},
@@ -41,6 +43,8 @@ use self::{
 
 use super::{iff::Image, mz::MzExe};
 
+pub mod art;
+pub mod asm;
 pub mod dm;
 pub mod flippers;
 mod gfx;
@@ -49,7 +53,7 @@ pub mod physics;
 pub mod script;
 pub mod sound;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Assets {
     pub table: TableId,
     pub exe: MzExe,
@@ -70,6 +74,7 @@ pub struct Assets {
     pub dm_fonts: EnumMap<DmFont, HashMap<u8, ArrayVec<u8, 13>>>,
     pub flippers: EntityVec<FlipperId, Flipper>,
     pub light_binds: EnumMap<LightBind, Vec<LightId>>,
+    #[serde(with = "super::bundle::serde_tower")]
     pub dm_tower: Option<Box<[[bool; 160]; 167]>>,
 
     pub transitions_down: Vec<Rect>,
@@ -91,6 +96,7 @@ pub struct Assets {
     pub cheats: Vec<Cheat>,
     pub effects: EnumMap<EffectBind, Option<Effect>>,
 
+    #[serde(with = "super::bundle::serde_array")]
     pub sine_table: [i16; 0xa00],
 
     pub score_jackpot_init: Bcd,
@@ -240,4 +246,39 @@ impl Assets {
             issue_ball_release_pos,
         }
     }
+
+    /// Replaces the table scripts with ones assembled from text (see [`asm`]).  All entry
+    /// points used by the game must still be present.
+    pub fn load_scripts(&mut self, src: &str) -> Result<(), AsmError> {
+        let new = asm::assemble(self, src)?;
+        let missing = |name: String| AsmError {
+            line: 0,
+            msg: format!("missing entry point {name}"),
+        };
+        for (bind, pos) in &self.script_binds {
+            if pos.is_some() && new.script_binds[bind].is_none() {
+                return Err(missing(format!("bind.{bind:?}")));
+            }
+        }
+        let mut effects = self.effects;
+        for (bind, effect) in &mut effects {
+            if let Some(effect) = effect {
+                if effect.script.is_some() {
+                    let name = format!("effect.{bind:?}");
+                    effect.script = Some(*new.labels.get(&name).ok_or_else(|| missing(name))?);
+                }
+            }
+        }
+        let mut cheats = self.cheats.clone();
+        for cheat in &mut cheats {
+            let name = cheat_label(&cheat.keys);
+            cheat.script = *new.labels.get(&name).ok_or_else(|| missing(name))?;
+        }
+        self.scripts = new.scripts;
+        self.msgs = new.msgs;
+        self.script_binds = new.script_binds;
+        self.effects = effects;
+        self.cheats = cheats;
+        Ok(())
+    }
 }
@@ -0,0 +1,329 @@
+//! Importing custom dot matrix art: animations from GIF or (A)PNG files, and message
+//! overrides from a text file.
+
+use std::fmt::Write;
+
+use unnamed_entity::{EntityId, EntityVec};
+
+use super::{
+    asm::{fmt_msg, tokenize, Token},
+    dm::{DmFont, DmPalette},
+    script::{special_chars, DmAnim, DmAnimFrame, DmAnimId, DmCoord, MsgId, Uop},
+    Assets,
+};
+
+pub type DmPixels = [[bool; 160]; 16];
+
+/// Scripts run once per 60Hz frame.
+pub const TICKS_PER_SEC: u32 = 60;
+
+#[derive(Clone, Debug)]
+pub struct ArtError(pub String);
+
+impl std::fmt::Display for ArtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ArtError {}
+
+#[derive(Clone, Debug)]
+pub struct DecodedAnim {
+    /// Frames with their duration in ticks.
+    pub frames: Vec<(DmPixels, u16)>,
+    /// How many times the whole animation is played.
+    pub repeats: u16,
+}
+
+/// Converts RGBA frames to dots.  A pixel is lit if it is closer to the table's "on"
+/// color than to the "off" one; transparent pixels keep the previous frame's state.
+struct Canvas<'a> {
+    pixels: DmPixels,
+    palette: &'a DmPalette,
+}
+
+impl Canvas<'_> {
+    fn blit(&mut self, x0: usize, y0: usize, width: usize, rgba: &[u8]) -> Result<(), ArtError> {
+        let dist = |c: (u8, u8, u8), px: &[u8]| {
+            (c.0 as i32 - px[0] as i32).pow(2)
+                + (c.1 as i32 - px[1] as i32).pow(2)
+                + (c.2 as i32 - px[2] as i32).pow(2)
+        };
+        for (i, px) in rgba.chunks_exact(4).enumerate() {
+            let x = x0 + i % width;
+            let y = y0 + i / width;
+            if x >= 160 || y >= 16 {
+                return Err(ArtError("frame does not fit in 160×16".into()));
+            }
+            if px[3] < 0x80 {
+                continue;
+            }
+            self.pixels[y][x] = dist(self.palette.color_on, px) < dist(self.palette.color_off, px);
+        }
+        Ok(())
+    }
+}
+
+/// Converts frame delays from 1/`from` s to 1/`to` s.  The frame boundaries are rounded
+/// rather than every delay, so that long animations don't drift.
+pub fn rescale_delays(delays: impl IntoIterator<Item = u32>, from: u32, to: u32) -> Vec<u32> {
+    let mut time = 0;
+    delays
+        .into_iter()
+        .map(|delay| {
+            let start = time * to / from;
+            time += delay;
+            time * to / from - start
+        })
+        .collect()
+}
+
+fn cs_to_ticks(frames: Vec<(DmPixels, u32)>) -> Vec<(DmPixels, u16)> {
+    let ticks = rescale_delays(frames.iter().map(|&(_, delay)| delay), 100, TICKS_PER_SEC);
+    frames
+        .into_iter()
+        .zip(ticks)
+        .map(|((pixels, _), ticks)| (pixels, ticks.clamp(1, 0xffff) as u16))
+        .collect()
+}
+
+fn decode_gif(data: &[u8], palette: &DmPalette) -> Result<DecodedAnim, ArtError> {
+    let err = |e: gif::DecodingError| ArtError(e.to_string());
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(data).map_err(err)?;
+    let mut canvas = Canvas {
+        pixels: [[false; 160]; 16],
+        palette,
+    };
+    let mut frames = vec![];
+    while let Some(frame) = decoder.read_next_frame().map_err(err)? {
+        canvas.blit(
+            frame.left as usize,
+            frame.top as usize,
+            frame.width as usize,
+            &frame.buffer,
+        )?;
+        frames.push((canvas.pixels, frame.delay as u32));
+    }
+    let repeats = match decoder.repeat() {
+        gif::Repeat::Infinite => u16::MAX,
+        gif::Repeat::Finite(n) => n.saturating_add(1),
+    };
+    Ok(DecodedAnim {
+        frames: cs_to_ticks(frames),
+        repeats,
+    })
+}
+
+fn decode_png(data: &[u8], palette: &DmPalette) -> Result<DecodedAnim, ArtError> {
+    let err = |e: png::DecodingError| ArtError(e.to_string());
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(err)?;
+    let (num_frames, repeats) = match reader.info().animation_control {
+        Some(actl) => (
+            actl.num_frames,
+            if actl.num_plays == 0 {
+                u16::MAX
+            } else {
+                actl.num_plays.min(0xffff) as u16
+            },
+        ),
+        None => (1, 1),
+    };
+    let mut buf = vec![0; reader.output_buffer_size()];
+    // The default image is not part of the animation unless it has its own frame control.
+    if reader.info().animation_control.is_some() && reader.info().frame_control.is_none() {
+        reader.next_frame(&mut buf).map_err(err)?;
+    }
+    let mut canvas = Canvas {
+        pixels: [[false; 160]; 16],
+        palette,
+    };
+    let mut frames = vec![];
+    for _ in 0..num_frames {
+        let out = reader.next_frame(&mut buf).map_err(err)?;
+        let fctl = reader.info().frame_control;
+        let (x0, y0) = fctl.map_or((0, 0), |fctl| {
+            (fctl.x_offset as usize, fctl.y_offset as usize)
+        });
+        let samples = out.color_type.samples();
+        let rgba: Vec<u8> = buf[..out.buffer_size()]
+            .chunks_exact(samples)
+            .flat_map(|px| match *px {
+                [l] => [l, l, l, 0xff],
+                [l, a] => [l, l, l, a],
+                [r, g, b] => [r, g, b, 0xff],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!(),
+            })
+            .collect();
+        canvas.blit(x0, y0, out.width as usize, &rgba)?;
+        let ticks = match fctl {
+            Some(fctl) => {
+                let den = if fctl.delay_den == 0 {
+                    100
+                } else {
+                    fctl.delay_den as u32
+                };
+                (fctl.delay_num as u32 * TICKS_PER_SEC / den).clamp(1, 0xffff) as u16
+            }
+            None => 1,
+        };
+        frames.push((canvas.pixels, ticks));
+    }
+    Ok(DecodedAnim { frames, repeats })
+}
+
+/// Decodes an animated GIF, an APNG, or a still PNG.  Frames must be at most 160×16.
+pub fn decode_dm_anim(data: &[u8], palette: &DmPalette) -> Result<DecodedAnim, ArtError> {
+    let anim = if data.starts_with(b"GIF8") {
+        decode_gif(data, palette)?
+    } else if data.starts_with(b"\x89PNG") {
+        decode_png(data, palette)?
+    } else {
+        return Err(ArtError("not a GIF or PNG file".into()));
+    };
+    if anim.frames.is_empty() {
+        return Err(ArtError("no frames".into()));
+    }
+    Ok(anim)
+}
+
+fn diff_frame(prev: Option<&DmPixels>, cur: &DmPixels) -> DmAnimFrame {
+    let mut res = vec![];
+    for y in 0..16 {
+        for x in 0..160 {
+            if prev.is_none_or(|prev| prev[y][x] != cur[y][x]) {
+                res.push((
+                    DmCoord {
+                        x: x as i16,
+                        y: y as i16,
+                    },
+                    cur[y][x],
+                ));
+            }
+        }
+    }
+    res.into()
+}
+
+fn is_special_char(c: u8) -> bool {
+    use special_chars::*;
+    let specials = [
+        (HIGH_SCORES, 12),
+        (BONUS_MULT_L, 2),
+        (BONUS_MULT_R, 2),
+        (CUR_PLAYER, 1),
+        (CUR_BALL, 1),
+        (TOTAL_PLAYERS, 1),
+        (NUM_CYCLONES, 3),
+        (NUM_CYCLONES_TARGET, 3),
+        (NUM_CYCLONES_TARGET_L, 3),
+    ];
+    specials
+        .into_iter()
+        .any(|(base, num)| (base..base + num).contains(&c))
+}
+
+impl Assets {
+    /// Replaces animation `idx`, or adds a new one if `idx` is one past the last existing
+    /// animation.
+    pub fn set_dm_anim(&mut self, idx: usize, anim: &DecodedAnim) -> Result<DmAnimId, ArtError> {
+        if idx > self.anims.len() {
+            return Err(ArtError(format!(
+                "animation {idx} would leave a gap after the {num} existing ones",
+                num = self.anims.len()
+            )));
+        }
+        // The previous contents of the display are unknown, so the first frame sets every
+        // dot.  The script engine shows the extra frame after the last one when looping
+        // back, so it is used to get from the last frame back to the first one.
+        let mut frames = vec![];
+        let mut prev = None;
+        for (pixels, delay) in &anim.frames {
+            let frame = self.anim_frames.push(diff_frame(prev, pixels));
+            frames.push((frame, *delay));
+            prev = Some(pixels);
+        }
+        let (first, first_delay) = &anim.frames[0];
+        let frame = self.anim_frames.push(diff_frame(prev, first));
+        frames.push((frame, *first_delay));
+        let anim = DmAnim {
+            repeats: anim.repeats.max(1),
+            restart: 0,
+            num_frames: anim.frames.len(),
+            frames,
+        };
+        if idx == self.anims.len() {
+            Ok(self.anims.push(anim))
+        } else {
+            let id = DmAnimId::from_idx(idx);
+            self.anims[id] = anim;
+            Ok(id)
+        }
+    }
+
+    /// Writes all messages in the format accepted by [`Assets::load_msgs`].
+    pub fn save_msgs(&self) -> String {
+        let mut res = String::new();
+        for (id, msg) in &self.msgs {
+            writeln!(res, "{idx} {msg}", idx = id.to_idx(), msg = fmt_msg(msg)).unwrap();
+        }
+        res
+    }
+
+    /// Overrides messages from a text file, with one `<index> "<text>"` pair per line.  Every
+    /// character must exist in all fonts the message is displayed with.
+    pub fn load_msgs(&mut self, src: &str) -> Result<(), ArtError> {
+        let mut fonts: EntityVec<MsgId, Vec<DmFont>> = self.msgs.ids().map(|_| vec![]).collect();
+        for uop in self.scripts.values() {
+            match *uop {
+                Uop::DmPuts(font, _, msg) => fonts[msg].push(font),
+                Uop::DmMsgScrollUp(msg, _) | Uop::DmMsgScrollDown(msg, _) | Uop::DmLongMsg(msg) => {
+                    fonts[msg].push(DmFont::H13)
+                }
+                _ => (),
+            }
+        }
+        let mut msgs = self.msgs.clone();
+        for (lidx, line) in src.lines().enumerate() {
+            let lnum = lidx + 1;
+            let err = |msg: String| ArtError(format!("line {lnum}: {msg}"));
+            let tokens = tokenize(line, lnum).map_err(|e| ArtError(e.to_string()))?;
+            let (idx, text) = match &tokens[..] {
+                [] => continue,
+                [Token::Num(idx), Token::Str(text)] => (*idx, text),
+                _ => return Err(err("expected <index> \"<text>\"".into())),
+            };
+            if idx < 0 || idx as usize >= msgs.len() {
+                return Err(err(format!("no message {idx}")));
+            }
+            let id = MsgId::from_idx(idx as usize);
+            for &c in text {
+                if c >= 0x80 {
+                    if !is_special_char(c) {
+                        return Err(err(format!("unknown special character \\x{c:02x}")));
+                    }
+                    continue;
+                }
+                if c == b' ' {
+                    continue;
+                }
+                for &font in &fonts[id] {
+                    if !self.dm_fonts[font].contains_key(&c) {
+                        return Err(err(format!(
+                            "character {chr:?} missing from font {font:?}",
+                            chr = c as char
+                        )));
+                    }
+                }
+            }
+            msgs[id] = text.clone().into();
+        }
+        self.msgs = msgs;
+        Ok(())
+    }
+}
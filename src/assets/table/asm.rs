@@ -0,0 +1,962 @@
+//! A text format for table scripts.
+//!
+//! Each line holds a label (`name:`), a uop (`Mnemonic arg, arg, ...`), or both.  `;` starts
+//! a comment.  Arguments are numbers, labels, quoted message text, enum names (`H13`,
+//! `Bonus`, `FlipperPress`), coordinates (`(x, y)`), or raw values (`Const(5000000)`,
+//! `Sfx(sample, period, channel)`, `Jingle(position, repeat, priority)`).
+//!
+//! Labels named `bind.<ScriptBind>`, `effect.<EffectBind>` and `cheat.<KEYS>` mark the entry
+//! points used by the game and must be kept when editing.
+
+use std::{collections::HashMap, fmt::Write};
+
+use enum_map::{enum_map, Enum, EnumMap};
+use unnamed_entity::{EntityId, EntityVec};
+
+use crate::bcd::Bcd;
+
+use super::{
+    dm::DmFont,
+    script::{DmAnimId, DmCoord, MsgId, ScriptBind, ScriptPosId, ScriptScore, Uop},
+    sound::{Jingle, JingleBind, Sfx, SfxBind},
+    Assets,
+};
+
+#[derive(Clone, Debug)]
+pub struct Scripts {
+    pub scripts: EntityVec<ScriptPosId, Uop>,
+    pub msgs: EntityVec<MsgId, Box<[u8]>>,
+    pub script_binds: EnumMap<ScriptBind, Option<ScriptPosId>>,
+    pub labels: HashMap<String, ScriptPosId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AsmError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl std::fmt::Display for AsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+pub fn cheat_label(keys: &[u8]) -> String {
+    let keys: String = keys
+        .iter()
+        .map(|&c| if c == b' ' { '_' } else { c as char })
+        .collect();
+    format!("cheat.{keys}")
+}
+
+fn enum_name<E: Enum + std::fmt::Debug>(name: &str) -> Option<E> {
+    (0..E::LENGTH)
+        .map(E::from_usize)
+        .find(|e| format!("{e:?}") == name)
+}
+
+pub(super) fn fmt_msg(msg: &[u8]) -> String {
+    let mut res = String::from("\"");
+    for &c in msg {
+        match c {
+            b'"' => res.push_str("\\\""),
+            b'\\' => res.push_str("\\\\"),
+            0x20..=0x7e => res.push(c as char),
+            _ => write!(res, "\\x{c:02x}").unwrap(),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn fmt_score(score: ScriptScore) -> String {
+    match score {
+        ScriptScore::Const(val) => {
+            let digits = val.to_ascii();
+            let digits = std::str::from_utf8(&digits).unwrap().trim_start();
+            format!("Const({digits})")
+        }
+        _ => format!("{score:?}"),
+    }
+}
+
+fn fmt_coord(pos: DmCoord) -> String {
+    format!("({}, {})", pos.x, pos.y)
+}
+
+pub fn disassemble(assets: &Assets) -> String {
+    let mut labels: EntityVec<ScriptPosId, Vec<String>> =
+        assets.scripts.ids().map(|_| vec![]).collect();
+    for (bind, pos) in &assets.script_binds {
+        if let Some(pos) = *pos {
+            labels[pos].push(format!("bind.{bind:?}"));
+        }
+    }
+    for (bind, effect) in &assets.effects {
+        if let Some(pos) = effect.and_then(|effect| effect.script) {
+            labels[pos].push(format!("effect.{bind:?}"));
+        }
+    }
+    for cheat in &assets.cheats {
+        labels[cheat.script].push(cheat_label(&cheat.keys));
+    }
+    for uop in assets.scripts.values() {
+        if let Uop::Jump(tgt)
+        | Uop::JccScoreZero(_, tgt)
+        | Uop::JccNoBonusMult(tgt)
+        | Uop::RepeatLoop(_, tgt)
+        | Uop::FinalScoreLoop(tgt) = *uop
+        {
+            if labels[tgt].is_empty() {
+                labels[tgt].push(format!("L{:04}", tgt.to_idx()));
+            }
+        }
+    }
+
+    let label = |pos: ScriptPosId| labels[pos][0].clone();
+    let msg = |msg: MsgId| fmt_msg(&assets.msgs[msg]);
+    let sfx = |sfx: Sfx| match assets
+        .sfx_binds
+        .iter()
+        .find(|(_, bsfx)| **bsfx == Some(sfx))
+    {
+        Some((bind, _)) => format!("{bind:?}"),
+        None => format!("Sfx({}, {}, {})", sfx.sample, sfx.period, sfx.channel),
+    };
+    let jingle = |jingle: Jingle| match assets
+        .jingle_binds
+        .iter()
+        .find(|(_, bjingle)| **bjingle == Some(jingle))
+    {
+        Some((bind, _)) => format!("{bind:?}"),
+        None => format!(
+            "Jingle({}, {}, {})",
+            jingle.position, jingle.repeat, jingle.priority
+        ),
+    };
+
+    let mut res = String::new();
+    writeln!(res, "; Scripts for {:?}", assets.table).unwrap();
+    for (pos, uop) in &assets.scripts {
+        if !labels[pos].is_empty() {
+            res.push('\n');
+            for name in &labels[pos] {
+                writeln!(res, "{name}:").unwrap();
+            }
+        }
+        let line = match *uop {
+            Uop::Delay(delay) => format!("Delay {delay}"),
+            Uop::DelayIfMultiplayer(delay) => format!("DelayIfMultiplayer {delay}"),
+            Uop::Jump(tgt) => format!("Jump {}", label(tgt)),
+            Uop::JccScoreZero(score, tgt) => {
+                format!("JccScoreZero {}, {}", fmt_score(score), label(tgt))
+            }
+            Uop::JccNoBonusMult(tgt) => format!("JccNoBonusMult {}", label(tgt)),
+            Uop::RepeatSetup(cnt) => format!("RepeatSetup {cnt}"),
+            Uop::RepeatLoop(cnt, tgt) => format!("RepeatLoop {cnt}, {}", label(tgt)),
+            Uop::FinalScoreLoop(tgt) => format!("FinalScoreLoop {}", label(tgt)),
+            Uop::PlaySfx(s, volume) => format!("PlaySfx {}, {volume}", sfx(s)),
+            Uop::PlayJingle(j) => format!("PlayJingle {}", jingle(j)),
+            Uop::SetMusic(position) => format!("SetMusic {position}"),
+            Uop::SetJingleTimeout(timeout) => format!("SetJingleTimeout {timeout}"),
+            Uop::ModeContinue(timeout, score) => {
+                format!("ModeContinue {timeout}, {}", fmt_score(score))
+            }
+            Uop::ModeStart(timeout, score) => {
+                format!("ModeStart {timeout}, {}", fmt_score(score))
+            }
+            Uop::ModeStartOrContinue(timeout, score) => {
+                format!("ModeStartOrContinue {timeout}, {}", fmt_score(score))
+            }
+            Uop::DmBlink(period) => format!("DmBlink {period}"),
+            Uop::DmState(state) => format!("DmState {state}"),
+            Uop::DmAnim(anim) => format!("DmAnim {}", anim.to_idx()),
+            Uop::DmPuts(font, pos, m) => {
+                format!("DmPuts {font:?}, {}, {}", fmt_coord(pos), msg(m))
+            }
+            Uop::DmPrintScore(font, center, pos, score) => format!(
+                "DmPrintScore {font:?}, {center}, {}, {}",
+                fmt_coord(pos),
+                fmt_score(score)
+            ),
+            Uop::DmMsgScrollUp(m, target) => format!("DmMsgScrollUp {}, {target}", msg(m)),
+            Uop::DmMsgScrollDown(m, target) => format!("DmMsgScrollDown {}, {target}", msg(m)),
+            Uop::DmLongMsg(m) => format!("DmLongMsg {}", msg(m)),
+            Uop::DmTowerHunt(pos) => format!("DmTowerHunt {pos}"),
+            // Everything else has no arguments.
+            _ => format!("{uop:?}"),
+        };
+        writeln!(res, "    {line}").unwrap();
+    }
+    res
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Token {
+    Ident(String),
+    Num(i64),
+    Str(Vec<u8>),
+    LParen,
+    RParen,
+    Comma,
+    Colon,
+}
+
+pub(super) fn tokenize(line: &str, lnum: usize) -> Result<Vec<Token>, AsmError> {
+    let err = |msg: String| AsmError { line: lnum, msg };
+    let mut res = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ';' => break,
+            ' ' | '\t' | '\r' => {
+                chars.next();
+            }
+            '(' | ')' | ',' | ':' => {
+                chars.next();
+                res.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => Token::Colon,
+                });
+            }
+            '"' => {
+                chars.next();
+                let mut s = vec![];
+                loop {
+                    match chars.next() {
+                        None => return Err(err("unterminated string".into())),
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('"') => s.push(b'"'),
+                            Some('\\') => s.push(b'\\'),
+                            Some('x') => {
+                                let hex: String = chars.by_ref().take(2).collect();
+                                let byte = u8::from_str_radix(&hex, 16)
+                                    .map_err(|_| err(format!("invalid escape \\x{hex}")))?;
+                                s.push(byte);
+                            }
+                            c => return Err(err(format!("invalid escape {c:?}"))),
+                        },
+                        Some(c) if c.is_ascii() => s.push(c as u8),
+                        Some(c) => return Err(err(format!("non-ASCII character {c:?}"))),
+                    }
+                }
+                res.push(Token::Str(s));
+            }
+            '-' | '0'..='9' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '-' || c.is_ascii_alphanumeric() {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let (neg, digits) = match s.strip_prefix('-') {
+                    Some(rest) => (true, rest),
+                    None => (false, &s[..]),
+                };
+                let val = match digits.strip_prefix("0x") {
+                    Some(hex) => i64::from_str_radix(hex, 16),
+                    None => digits.parse(),
+                }
+                .map_err(|_| err(format!("invalid number {s}")))?;
+                res.push(Token::Num(if neg { -val } else { val }));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == '.' => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                        s.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                res.push(Token::Ident(s));
+            }
+            c => return Err(err(format!("unexpected character {c:?}"))),
+        }
+    }
+    Ok(res)
+}
+
+#[derive(Clone, Debug)]
+enum Arg {
+    Num(i64),
+    Str(Vec<u8>),
+    Name(String, Option<Vec<Arg>>),
+    Tuple(Vec<Arg>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn err(&self, msg: impl Into<String>) -> AsmError {
+        AsmError {
+            line: self.line,
+            msg: msg.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let res = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        res
+    }
+
+    fn arg_list(&mut self) -> Result<Vec<Arg>, AsmError> {
+        // Assumes the opening paren was already consumed.
+        let mut res = vec![];
+        if self.peek() == Some(&Token::RParen) {
+            self.next();
+            return Ok(res);
+        }
+        loop {
+            res.push(self.arg()?);
+            match self.next() {
+                Some(Token::Comma) => (),
+                Some(Token::RParen) => return Ok(res),
+                _ => return Err(self.err("expected , or )")),
+            }
+        }
+    }
+
+    fn arg(&mut self) -> Result<Arg, AsmError> {
+        match self.next() {
+            Some(Token::Num(n)) => Ok(Arg::Num(n)),
+            Some(Token::Str(s)) => Ok(Arg::Str(s)),
+            Some(Token::LParen) => Ok(Arg::Tuple(self.arg_list()?)),
+            Some(Token::Ident(name)) => {
+                if self.peek() == Some(&Token::LParen) {
+                    self.next();
+                    Ok(Arg::Name(name, Some(self.arg_list()?)))
+                } else {
+                    Ok(Arg::Name(name, None))
+                }
+            }
+            _ => Err(self.err("expected argument")),
+        }
+    }
+}
+
+struct Line {
+    line: usize,
+    mnemonic: String,
+    args: Vec<Arg>,
+}
+
+struct Assembler<'a> {
+    assets: &'a Assets,
+    labels: HashMap<String, ScriptPosId>,
+    msgs: EntityVec<MsgId, Box<[u8]>>,
+    msg_ids: HashMap<Box<[u8]>, MsgId>,
+    line: usize,
+}
+
+impl Assembler<'_> {
+    fn err(&self, msg: impl Into<String>) -> AsmError {
+        AsmError {
+            line: self.line,
+            msg: msg.into(),
+        }
+    }
+
+    fn num<T: TryFrom<i64>>(&self, arg: &Arg) -> Result<T, AsmError> {
+        match *arg {
+            Arg::Num(n) => T::try_from(n).map_err(|_| self.err(format!("{n} out of range"))),
+            _ => Err(self.err("expected a number")),
+        }
+    }
+
+    fn name<'b>(&self, arg: &'b Arg) -> Result<&'b str, AsmError> {
+        match arg {
+            Arg::Name(name, None) => Ok(name),
+            _ => Err(self.err("expected a name")),
+        }
+    }
+
+    fn enum_arg<E: Enum + std::fmt::Debug>(&self, arg: &Arg, what: &str) -> Result<E, AsmError> {
+        let name = self.name(arg)?;
+        enum_name(name).ok_or_else(|| self.err(format!("unknown {what} {name}")))
+    }
+
+    fn label(&self, arg: &Arg) -> Result<ScriptPosId, AsmError> {
+        let name = self.name(arg)?;
+        self.labels
+            .get(name)
+            .copied()
+            .ok_or_else(|| self.err(format!("undefined label {name}")))
+    }
+
+    fn bool(&self, arg: &Arg) -> Result<bool, AsmError> {
+        match self.name(arg)? {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.err("expected true or false")),
+        }
+    }
+
+    fn coord(&self, arg: &Arg) -> Result<DmCoord, AsmError> {
+        match arg {
+            Arg::Tuple(xy) if xy.len() == 2 => Ok(DmCoord {
+                x: self.num(&xy[0])?,
+                y: self.num(&xy[1])?,
+            }),
+            _ => Err(self.err("expected (x, y)")),
+        }
+    }
+
+    fn msg(&mut self, arg: &Arg) -> Result<MsgId, AsmError> {
+        let Arg::Str(msg) = arg else {
+            return Err(self.err("expected a message"));
+        };
+        let msg: Box<[u8]> = msg.clone().into();
+        if let Some(&id) = self.msg_ids.get(&msg) {
+            return Ok(id);
+        }
+        let id = self.msgs.push(msg.clone());
+        self.msg_ids.insert(msg, id);
+        Ok(id)
+    }
+
+    fn anim(&self, arg: &Arg) -> Result<DmAnimId, AsmError> {
+        let idx: usize = self.num(arg)?;
+        if idx >= self.assets.anims.len() {
+            return Err(self.err(format!("no such animation {idx}")));
+        }
+        Ok(DmAnimId::from_idx(idx))
+    }
+
+    fn score(&self, arg: &Arg) -> Result<ScriptScore, AsmError> {
+        Ok(match arg {
+            Arg::Name(name, Some(args)) if name == "Const" && args.len() == 1 => {
+                let val: u64 = self.num(&args[0])?;
+                let digits = val.to_string();
+                if digits.len() > Bcd::DIGITS {
+                    return Err(self.err(format!("score {val} too large")));
+                }
+                ScriptScore::Const(Bcd::from_ascii(digits.as_bytes()))
+            }
+            Arg::Name(name, Some(args)) if name == "HighScore" && args.len() == 1 => {
+                let idx = self.num(&args[0])?;
+                if idx >= 4 {
+                    return Err(self.err(format!("no such high score {idx}")));
+                }
+                ScriptScore::HighScore(idx)
+            }
+            Arg::Name(name, None) => match &name[..] {
+                "Bonus" => ScriptScore::Bonus,
+                "ModeHit" => ScriptScore::ModeHit,
+                "ModeRamp" => ScriptScore::ModeRamp,
+                "Jackpot" => ScriptScore::Jackpot,
+                "CycloneIncr" => ScriptScore::CycloneIncr,
+                "NumCyclone" => ScriptScore::NumCyclone,
+                "CycloneBonus" => ScriptScore::CycloneBonus,
+                "PartyTunnelSkillShot" => ScriptScore::PartyTunnelSkillShot,
+                "PartyCycloneSkillShot" => ScriptScore::PartyCycloneSkillShot,
+                "ShowRaisingMillions" => ScriptScore::ShowRaisingMillions,
+                "ShowSpinWheel" => ScriptScore::ShowSpinWheel,
+                "ShowCashpot" => ScriptScore::ShowCashpot,
+                "ShowCashpotX5" => ScriptScore::ShowCashpotX5,
+                "StonesSkillShot" => ScriptScore::StonesSkillShot,
+                "StonesMillionPlus" => ScriptScore::StonesMillionPlus,
+                "StonesVault" => ScriptScore::StonesVault,
+                "StonesWell" => ScriptScore::StonesWell,
+                "StonesTowerBonus" => ScriptScore::StonesTowerBonus,
+                _ => return Err(self.err(format!("unknown score {name}"))),
+            },
+            _ => return Err(self.err("expected a score")),
+        })
+    }
+
+    fn sfx(&self, arg: &Arg) -> Result<Sfx, AsmError> {
+        match arg {
+            Arg::Name(name, Some(args)) if name == "Sfx" && args.len() == 3 => Ok(Sfx {
+                sample: self.num(&args[0])?,
+                period: self.num(&args[1])?,
+                channel: self.num(&args[2])?,
+            }),
+            _ => {
+                let bind: SfxBind = self.enum_arg(arg, "sfx")?;
+                self.assets.sfx_binds[bind]
+                    .ok_or_else(|| self.err(format!("sfx {bind:?} not present on this table")))
+            }
+        }
+    }
+
+    fn jingle(&self, arg: &Arg) -> Result<Jingle, AsmError> {
+        match arg {
+            Arg::Name(name, Some(args)) if name == "Jingle" && args.len() == 3 => Ok(Jingle {
+                position: self.num(&args[0])?,
+                repeat: self.num(&args[1])?,
+                priority: self.num(&args[2])?,
+            }),
+            _ => {
+                let bind: JingleBind = self.enum_arg(arg, "jingle")?;
+                self.assets.jingle_binds[bind]
+                    .ok_or_else(|| self.err(format!("jingle {bind:?} not present on this table")))
+            }
+        }
+    }
+
+    fn uop(&mut self, line: &Line) -> Result<Uop, AsmError> {
+        self.line = line.line;
+        let args = &line.args[..];
+        let nargs = match &line.mnemonic[..] {
+            "Delay" | "DelayIfMultiplayer" | "Jump" | "JccNoBonusMult" | "RepeatSetup"
+            | "FinalScoreLoop" | "PlayJingle" | "SetMusic" | "SetJingleTimeout" | "DmBlink"
+            | "DmState" | "DmAnim" | "DmLongMsg" | "DmTowerHunt" => 1,
+            "JccScoreZero"
+            | "RepeatLoop"
+            | "PlaySfx"
+            | "ModeContinue"
+            | "ModeStart"
+            | "ModeStartOrContinue"
+            | "DmMsgScrollUp"
+            | "DmMsgScrollDown" => 2,
+            "DmPuts" => 3,
+            "DmPrintScore" => 4,
+            _ => 0,
+        };
+        if args.len() != nargs {
+            if nargs == 0 {
+                // Everything without an entry above lands here, so make sure it's a uop.
+                self.uop(&Line {
+                    line: line.line,
+                    mnemonic: line.mnemonic.clone(),
+                    args: vec![],
+                })?;
+            }
+            return Err(self.err(format!(
+                "{} takes {nargs} arguments, got {}",
+                line.mnemonic,
+                args.len()
+            )));
+        }
+        Ok(match &line.mnemonic[..] {
+            "End" => Uop::End,
+            "Noop" => Uop::Noop,
+            "Delay" => Uop::Delay(self.num(&args[0])?),
+            "DelayIfMultiplayer" => Uop::DelayIfMultiplayer(self.num(&args[0])?),
+            "Halt" => Uop::Halt,
+            "Jump" => Uop::Jump(self.label(&args[0])?),
+            "JccScoreZero" => Uop::JccScoreZero(self.score(&args[0])?, self.label(&args[1])?),
+            "JccNoBonusMult" => Uop::JccNoBonusMult(self.label(&args[0])?),
+            "RepeatSetup" => Uop::RepeatSetup(self.num(&args[0])?),
+            "RepeatLoop" => Uop::RepeatLoop(self.num(&args[0])?, self.label(&args[1])?),
+            "FinalScoreSetup" => Uop::FinalScoreSetup,
+            "FinalScoreLoop" => Uop::FinalScoreLoop(self.label(&args[0])?),
+            "ConfirmQuit" => Uop::ConfirmQuit,
+
+            "WaitWhileGameStarting" => Uop::WaitWhileGameStarting,
+            "ExtraBall" => Uop::ExtraBall,
+            "SetupPartyOn" => Uop::SetupPartyOn,
+            "SetupShootAgain" => Uop::SetupShootAgain,
+            "SetSpecialPlungerEvent" => Uop::SetSpecialPlungerEvent,
+            "IssueBall" => Uop::IssueBall,
+
+            "MultiplyBonus" => Uop::MultiplyBonus,
+            "AccBonusCyclones" => Uop::AccBonusCyclones,
+            "AccBonusModeHit" => Uop::AccBonusModeHit,
+            "AccBonusModeRamp" => Uop::AccBonusModeRamp,
+            "AccBonus" => Uop::AccBonus,
+            "CheckTopScore" => Uop::CheckTopScore,
+            "NextBallIfMatched" => Uop::NextBallIfMatched,
+            "NextBall" => Uop::NextBall,
+
+            "Match" => Uop::Match,
+            "CheckMatch" => Uop::CheckMatch,
+            "RecordHighScores" => Uop::RecordHighScores,
+            "GameOver" => Uop::GameOver,
+
+            "PlaySfx" => Uop::PlaySfx(self.sfx(&args[0])?, self.num(&args[1])?),
+            "PlayJingle" => Uop::PlayJingle(self.jingle(&args[0])?),
+            "SetMusic" => Uop::SetMusic(self.num(&args[0])?),
+            "SetJingleTimeout" => Uop::SetJingleTimeout(self.num(&args[0])?),
+            "WaitJingle" => Uop::WaitJingle,
+            "WaitJingleTimeout" => Uop::WaitJingleTimeout,
+
+            "ModeContinue" => Uop::ModeContinue(self.num(&args[0])?, self.score(&args[1])?),
+            "ModeStart" => Uop::ModeStart(self.num(&args[0])?, self.score(&args[1])?),
+            "ModeStartOrContinue" => {
+                Uop::ModeStartOrContinue(self.num(&args[0])?, self.score(&args[1])?)
+            }
+
+            "DmBlink" => Uop::DmBlink(self.num(&args[0])?),
+            "DmStopBlink" => Uop::DmStopBlink,
+            "DmState" => Uop::DmState(self.bool(&args[0])?),
+            "DmClear" => Uop::DmClear,
+            "DmWipeDown" => Uop::DmWipeDown,
+            "DmWipeRight" => Uop::DmWipeRight,
+            "DmWipeDownStriped" => Uop::DmWipeDownStriped,
+            "DmAnim" => Uop::DmAnim(self.anim(&args[0])?),
+            "DmPuts" => Uop::DmPuts(
+                self.enum_arg(&args[0], "font")?,
+                self.coord(&args[1])?,
+                self.msg(&args[2])?,
+            ),
+            "DmPrintScore" => Uop::DmPrintScore(
+                self.enum_arg::<DmFont>(&args[0], "font")?,
+                self.bool(&args[1])?,
+                self.coord(&args[2])?,
+                self.score(&args[3])?,
+            ),
+            "DmMsgScrollUp" => Uop::DmMsgScrollUp(self.msg(&args[0])?, self.num(&args[1])?),
+            "DmMsgScrollDown" => Uop::DmMsgScrollDown(self.msg(&args[0])?, self.num(&args[1])?),
+            "DmLongMsg" => Uop::DmLongMsg(self.msg(&args[0])?),
+            "DmTowerHunt" => Uop::DmTowerHunt(self.num(&args[0])?),
+
+            "PartyArcadeReady" => Uop::PartyArcadeReady,
+            "PartySecretDrop" => Uop::PartySecretDrop,
+
+            "SpeedStartTurbo" => Uop::SpeedStartTurbo,
+            "SpeedCheckTurboCont" => Uop::SpeedCheckTurboCont,
+            "SpeedClearFlagMode" => Uop::SpeedClearFlagMode,
+
+            "ShowSpinWheelEnd" => Uop::ShowSpinWheelEnd,
+            "ShowBlinkMoneyMania" => Uop::ShowBlinkMoneyMania,
+            "ShowEndMoneyMania" => Uop::ShowEndMoneyMania,
+
+            "StonesTowerEject" => Uop::StonesTowerEject,
+            "StonesVaultEject" => Uop::StonesVaultEject,
+            "StonesWellEject" => Uop::StonesWellEject,
+            "StonesTiltEject" => Uop::StonesTiltEject,
+            "StonesSetFlagMode" => Uop::StonesSetFlagMode,
+            "StonesSetFlagModeRamp" => Uop::StonesSetFlagModeRamp,
+            "StonesSetFlagModeHit" => Uop::StonesSetFlagModeHit,
+            "StonesClearFlagMode" => Uop::StonesClearFlagMode,
+            "StonesClearFlagModeRamp" => Uop::StonesClearFlagModeRamp,
+            "StonesClearFlagModeHit" => Uop::StonesClearFlagModeHit,
+            "StonesEndMode" => Uop::StonesEndMode,
+            "StonesEndGrimReaper" => Uop::StonesEndGrimReaper,
+
+            name => return Err(self.err(format!("unknown uop {name}"))),
+        })
+    }
+}
+
+/// Assembles scripts for the given table.  The assets are used to look up named sounds and
+/// to validate animation references.
+pub fn assemble(assets: &Assets, src: &str) -> Result<Scripts, AsmError> {
+    let mut labels = HashMap::new();
+    let mut lines = vec![];
+    for (lidx, text) in src.lines().enumerate() {
+        let mut parser = Parser {
+            tokens: tokenize(text, lidx + 1)?,
+            pos: 0,
+            line: lidx + 1,
+        };
+        if let (Some(Token::Ident(name)), Some(Token::Colon)) =
+            (parser.tokens.first(), parser.tokens.get(1))
+        {
+            let pos = ScriptPosId::from_idx(lines.len());
+            if labels.insert(name.clone(), pos).is_some() {
+                return Err(parser.err(format!("duplicate label {name}")));
+            }
+            parser.pos = 2;
+        }
+        let Some(token) = parser.next() else {
+            continue;
+        };
+        let Token::Ident(mnemonic) = token else {
+            return Err(parser.err("expected uop"));
+        };
+        let mut args = vec![];
+        if parser.peek().is_some() {
+            loop {
+                args.push(parser.arg()?);
+                match parser.next() {
+                    None => break,
+                    Some(Token::Comma) => (),
+                    Some(_) => return Err(parser.err("expected ,")),
+                }
+            }
+        }
+        lines.push(Line {
+            line: lidx + 1,
+            mnemonic,
+            args,
+        });
+    }
+
+    // The table's messages keep their numbers, so that message files made for it still
+    // apply, and new ones go after them.
+    let mut msg_ids = HashMap::new();
+    for (id, msg) in &assets.msgs {
+        msg_ids.entry(msg.clone()).or_insert(id);
+    }
+    let mut asm = Assembler {
+        assets,
+        labels,
+        msgs: assets.msgs.clone(),
+        msg_ids,
+        line: 0,
+    };
+    let mut scripts = EntityVec::new();
+    for line in &lines {
+        scripts.push(asm.uop(line)?);
+    }
+    if scripts.is_empty() {
+        return Err(AsmError {
+            line: 0,
+            msg: "no uops".into(),
+        });
+    }
+    for (name, &pos) in &asm.labels {
+        if pos == scripts.next_id() {
+            return Err(AsmError {
+                line: 0,
+                msg: format!("label {name} at end of file"),
+            });
+        }
+    }
+    let mut script_binds = enum_map! { _ => None };
+    for (name, &pos) in &asm.labels {
+        if let Some(bind) = name.strip_prefix("bind.") {
+            let Some(bind) = enum_name::<ScriptBind>(bind) else {
+                return Err(AsmError {
+                    line: 0,
+                    msg: format!("unknown script bind {bind}"),
+                });
+            };
+            script_binds[bind] = Some(pos);
+        }
+    }
+    Ok(Scripts {
+        scripts,
+        msgs: asm.msgs,
+        script_binds,
+        labels: asm.labels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use enum_map::enum_map;
+    use ndarray::Array2;
+
+    use crate::{
+        assets::{iff::Image, mz::MzExe},
+        config::TableId,
+        table::scenario::table_data,
+    };
+
+    use super::super::{
+        dm::DmPalette,
+        script::{special_chars, Cheat, CheatEffect, DmAnim, Effect, EffectBind, EffectSound},
+    };
+    use super::*;
+
+    /// Assets with nothing in them but a few scripts, messages and the things scripts refer to.
+    fn script_assets() -> Assets {
+        let image = || Image {
+            data: Array2::zeros((0, 0)),
+            cmap: vec![],
+        };
+        let pos = ScriptPosId::from_idx;
+        let msg = MsgId::from_idx;
+        let sfx = Sfx {
+            sample: 4,
+            period: 5,
+            channel: 1,
+        };
+        let mut sfx_binds = enum_map! { _ => None };
+        sfx_binds[SfxBind::from_usize(0)] = Some(sfx);
+        let mut script_binds = enum_map! { _ => None };
+        script_binds[ScriptBind::Init] = Some(pos(0));
+        script_binds[ScriptBind::Attract] = Some(pos(0));
+        let mut effects = enum_map! { _ => None };
+        effects[EffectBind::from_usize(0)] = Some(Effect {
+            sound: EffectSound::Silent(0),
+            score_main: Bcd::ZERO,
+            score_bonus: Bcd::ZERO,
+            script: Some(pos(9)),
+        });
+        Assets {
+            table: TableId::Table1,
+            exe: MzExe {
+                image: vec![],
+                relocs: vec![],
+                cs: 0,
+                ip: 0,
+                ss: 0,
+                sp: 0,
+                ds: 0,
+            },
+            main_board: image(),
+            spring: image(),
+            ball: image(),
+            occmaps: enum_map! { _ => Array2::zeros((0, 0)) },
+            physmaps: enum_map! { _ => Array2::zeros((0, 0)) },
+            physmap_patches: enum_map! { _ => None },
+            ramps: vec![],
+            ball_outline: vec![],
+            ball_outline_by_angle: vec![],
+            lights: EntityVec::new(),
+            attract_lights: EntityVec::new(),
+            dm_palette: DmPalette {
+                index_off: 0,
+                index_on: 1,
+                color_off: (0, 0, 0),
+                color_on: (0xff, 0xff, 0xff),
+            },
+            dm_fonts: enum_map! { _ => HashMap::new() },
+            flippers: EntityVec::new(),
+            light_binds: enum_map! { _ => vec![] },
+            dm_tower: None,
+            transitions_down: vec![],
+            transitions_up: vec![],
+            bumpers: EntityVec::new(),
+            roll_triggers: enum_map! { _ => vec![] },
+            roll_triggers_tilt: enum_map! { _ => vec![] },
+            hit_triggers: vec![],
+            jingle_binds: enum_map! { _ => None },
+            sfx_binds,
+            position_jingle_start: 0,
+            scripts: [
+                Uop::DmPuts(DmFont::H13, DmCoord { x: 0, y: 1 }, msg(0)),
+                Uop::PlaySfx(sfx, 64),
+                Uop::PlaySfx(
+                    Sfx {
+                        sample: 1,
+                        period: 2,
+                        channel: 3,
+                    },
+                    10,
+                ),
+                Uop::PlayJingle(Jingle {
+                    position: 0x12,
+                    repeat: 0,
+                    priority: 5,
+                }),
+                Uop::RepeatSetup(3),
+                Uop::DmLongMsg(msg(1)),
+                Uop::RepeatLoop(3, pos(5)),
+                Uop::JccScoreZero(ScriptScore::Const(Bcd::from_ascii(b"5000000")), pos(9)),
+                Uop::Jump(pos(0)),
+                Uop::DmAnim(DmAnimId::from_idx(0)),
+                Uop::Halt,
+                Uop::DmMsgScrollUp(msg(0), 3),
+                Uop::End,
+            ]
+            .into_iter()
+            .collect(),
+            msgs: [
+                &b"SAY \"HI\" \\O/"[..],
+                &[special_chars::HIGH_SCORES, b' ', b'1'][..],
+            ]
+            .into_iter()
+            .map(Into::into)
+            .collect(),
+            anims: [DmAnim {
+                repeats: 1,
+                restart: 0,
+                num_frames: 0,
+                frames: vec![],
+            }]
+            .into_iter()
+            .collect(),
+            anim_frames: EntityVec::new(),
+            script_binds,
+            cheats: vec![Cheat {
+                keys: b"EXTRA BALLS".as_slice().into(),
+                script: pos(11),
+                effect: CheatEffect::Balls,
+            }],
+            effects,
+            sine_table: [0; 0xa00],
+            score_jackpot_init: Bcd::ZERO,
+            score_jackpot_incr: Bcd::ZERO,
+            score_mode_hit_incr: Bcd::ZERO,
+            score_mode_ramp_incr: Bcd::ZERO,
+            issue_ball_pos: (0, 0),
+            issue_ball_release_pos: (0, 0),
+        }
+    }
+
+    /// Disassembles the scripts and assembles them again.
+    fn round_trip(assets: &Assets) -> (String, Scripts) {
+        let src = disassemble(assets);
+        let scripts = assemble(assets, &src).unwrap_or_else(|e| panic!("{e}\n{src}"));
+        (src, scripts)
+    }
+
+    #[test]
+    fn round_trip_scripts() {
+        let assets = script_assets();
+        let (src, scripts) = round_trip(&assets);
+        assert_eq!(
+            format!("{:?}", scripts.scripts),
+            format!("{:?}", assets.scripts),
+            "{src}"
+        );
+        assert_eq!(scripts.msgs, assets.msgs);
+        assert_eq!(scripts.script_binds, assets.script_binds);
+        assert_eq!(
+            scripts.labels[&format!("effect.{:?}", EffectBind::from_usize(0))],
+            ScriptPosId::from_idx(9)
+        );
+        assert_eq!(
+            scripts.labels[&cheat_label(b"EXTRA BALLS")],
+            ScriptPosId::from_idx(11)
+        );
+    }
+
+    #[test]
+    fn new_messages_go_last() {
+        let assets = script_assets();
+        let src = disassemble(&assets).replace("\"SAY \\\"HI\\\" \\\\O/\"", "\"BYE\"");
+        let scripts = assemble(&assets, &src).unwrap_or_else(|e| panic!("{e}\n{src}"));
+        assert_eq!(scripts.msgs.len(), assets.msgs.len() + 1);
+        assert_eq!(&scripts.msgs.values().last().unwrap()[..], b"BYE");
+    }
+
+    #[test]
+    fn unknown_uop() {
+        let err = assemble(&script_assets(), "Bogus 1").unwrap_err();
+        assert_eq!(err.msg, "unknown uop Bogus");
+        let err = assemble(&script_assets(), "Halt 1").unwrap_err();
+        assert_eq!(err.msg, "Halt takes 0 arguments, got 1");
+    }
+
+    /// Identical messages are merged, so real tables, which have some twice, are compared by
+    /// disassembling the result again.
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn round_trip_tables() {
+        for table in [
+            TableId::Table1,
+            TableId::Table2,
+            TableId::Table3,
+            TableId::Table4,
+        ] {
+            let assets = table_data(table).assets;
+            let (src, scripts) = round_trip(&assets);
+            assert_eq!(scripts.scripts.len(), assets.scripts.len(), "{table:?}");
+            assert_eq!(scripts.script_binds, assets.script_binds, "{table:?}");
+            assert_eq!(scripts.msgs, assets.msgs, "{table:?}");
+            let reassembled = Assets {
+                scripts: scripts.scripts,
+                msgs: scripts.msgs,
+                script_binds: scripts.script_binds,
+                ..(*assets).clone()
+            };
+            assert!(disassemble(&reassembled) == src, "{table:?}");
+        }
+    }
+}
@@ -2,10 +2,11 @@ use std::collections::HashMap;
 
 use arrayvec::ArrayVec;
 use enum_map::{enum_map, Enum, EnumMap};
+use serde::{Deserialize, Serialize};
 
 use crate::{assets::mz::MzExe, config::TableId};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DmPalette {
     pub index_off: u8,
     pub index_on: u8,
@@ -13,7 +14,7 @@ pub struct DmPalette {
     pub color_on: (u8, u8, u8),
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum, Serialize, Deserialize)]
 pub enum DmFont {
     H5,
     H8,
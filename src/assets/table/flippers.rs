@@ -1,5 +1,6 @@
 use enum_map::{Enum, EnumMap};
 use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
 use unnamed_entity::{entity_id, EntityVec};
 
 use crate::{
@@ -9,7 +10,7 @@ use crate::{
 
 use super::physics::{Layer, Rect};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Enum)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Enum, Serialize, Deserialize)]
 pub enum FlipperSide {
     Left,
     Right,
@@ -19,7 +20,7 @@ entity_id! {
     pub id FlipperId u8;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Flipper {
     pub side: FlipperSide,
     pub rect_pos: (u16, u16),
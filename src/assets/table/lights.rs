@@ -1,4 +1,5 @@
 use enum_map::{enum_map, Enum, EnumMap};
+use serde::{Deserialize, Serialize};
 use unnamed_entity::{entity_id, EntityId, EntityVec};
 
 use crate::{assets::mz::MzExe, config::TableId};
@@ -8,13 +9,13 @@ entity_id! {
     pub id AttractLightId u8;
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Light {
     pub base_index: u8,
     pub colors: Vec<(u8, u8, u8)>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AttractLight {
     pub ctr_reset: u16,
     pub ctr_off: u16,
@@ -22,7 +23,7 @@ pub struct AttractLight {
     pub light: LightId,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum, Serialize, Deserialize)]
 pub enum LightBind {
     PartyPuke,
     PartyDrop,
@@ -2,6 +2,7 @@
 
 use enum_map::{enum_map, Enum, EnumMap};
 use ndarray::prelude::*;
+use serde::{Deserialize, Serialize};
 use unnamed_entity::{entity_id, EntityVec};
 
 use crate::{assets::mz::MzExe, bcd::Bcd, config::TableId};
@@ -12,19 +13,21 @@ entity_id! {
     pub id BumperId u8, reserve 1;
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Enum)]
+#[derive(
+    Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Enum, Serialize, Deserialize,
+)]
 pub enum Layer {
     Ground,
     Overhead,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Rect {
     pub xy_min: (i16, i16),
     pub xy_max: (i16, i16),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Bumper {
     pub is_kicker: bool,
     pub rect: Rect,
@@ -32,7 +35,7 @@ pub struct Bumper {
     pub score: Bcd,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum HitTrigger {
     PartyArcadeButton,
     PartyDuck(u8),
@@ -45,13 +48,13 @@ pub enum HitTrigger {
     StonesStone(u8),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct HitTriggerArea {
     pub rect: Rect,
     pub kind: HitTrigger,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum RollTrigger {
     Dummy,
     PlungerBottom,
@@ -130,7 +133,7 @@ pub enum RollTrigger {
     StonesTowerTilt,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct RollTriggerArea {
     pub rect: Rect,
     pub kind: RollTrigger,
@@ -145,7 +148,7 @@ impl Rect {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PhysmapPatch {
     pub layer: Layer,
     pub pos: (u16, u16),
@@ -153,7 +156,7 @@ pub struct PhysmapPatch {
     pub dropped: Array2<u8>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum, Serialize, Deserialize)]
 pub enum PhysmapBind {
     PartyGateSkyride,
     PartyHitDuck0,
@@ -175,7 +178,7 @@ pub enum PhysmapBind {
     StonesGateKickback,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Ramp {
     pub accel: (i16, i16),
     pub accel_hires: (i16, i16),
@@ -616,7 +619,7 @@ pub(super) fn extract_ramps(exe: &MzExe, table: TableId) -> Vec<Ramp> {
         .collect()
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct BallOutlinePixel {
     pub x: i16,
     pub y: i16,
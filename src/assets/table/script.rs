@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use enum_map::{enum_map, Enum, EnumMap};
+use serde::{Deserialize, Serialize};
 use unnamed_entity::{entity_id, EntityMap, EntityVec};
 
 use crate::{assets::mz::MzExe, bcd::Bcd, config::TableId};
@@ -17,7 +18,7 @@ entity_id! {
     pub id MsgId u16;
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Uop {
     End,
 
@@ -106,13 +107,13 @@ pub enum Uop {
     StonesEndGrimReaper,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DmCoord {
     pub x: i16,
     pub y: i16,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ScriptScore {
     Bonus,
     ModeHit,
@@ -136,7 +137,7 @@ pub enum ScriptScore {
     StonesTowerBonus,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DmAnim {
     pub repeats: u16,
     pub restart: usize,
@@ -144,7 +145,7 @@ pub struct DmAnim {
     pub frames: Vec<(DmAnimFrameId, u16)>,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum, Serialize, Deserialize)]
 pub enum ScriptBind {
     Init,
     Attract,
@@ -189,7 +190,7 @@ pub enum ScriptBind {
     StonesModeRampContinue,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum CheatEffect {
     None,
     Tilt,
@@ -198,14 +199,14 @@ pub enum CheatEffect {
     Reset,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cheat {
     pub keys: Box<[u8]>,
     pub script: ScriptPosId,
     pub effect: CheatEffect,
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Enum, Serialize, Deserialize)]
 pub enum EffectBind {
     Drained,
 
@@ -401,13 +402,13 @@ pub enum EffectBind {
     StonesScreamsTo5M,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum EffectSound {
     Jingle(Jingle),
     Silent(u8),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Effect {
     pub sound: EffectSound,
     pub score_main: Bcd,
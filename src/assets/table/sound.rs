@@ -1,15 +1,16 @@
 use enum_map::{enum_map, Enum, EnumMap};
+use serde::{Deserialize, Serialize};
 
 use crate::{assets::mz::MzExe, config::TableId};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Jingle {
     pub position: u8,
     pub repeat: u8,
     pub priority: u8,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Enum, Serialize, Deserialize)]
 pub enum JingleBind {
     // General
     Silence,
@@ -41,14 +42,14 @@ pub enum JingleBind {
     StonesTowerHuntEnd,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Sfx {
     pub sample: u8,
     pub period: u8,
     pub channel: u8,
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Enum)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Enum, Serialize, Deserialize)]
 pub enum SfxBind {
     // General
     FlipperPress,
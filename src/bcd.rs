@@ -1,4 +1,8 @@
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Copy, Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd, Debug, Serialize, Deserialize,
+)]
 pub struct Bcd {
     pub digits: [u8; Self::DIGITS],
 }
@@ -50,6 +54,12 @@ impl Bcd {
         }
     }
 
+    pub fn to_u64(self) -> u64 {
+        self.digits
+            .iter()
+            .fold(0, |acc, &digit| acc * 10 + u64::from(digit))
+    }
+
     pub fn leading_zeros(self) -> usize {
         let mut res = 0;
         while self.digits[res] == 0 && res < 12 {
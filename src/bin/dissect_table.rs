@@ -1,8 +1,14 @@
 use clap::Parser;
+use enum_map::Enum;
 use ndarray::{s, Array2};
 use pfr::assets::iff::Image;
-use pfr::assets::table::{physics::Layer, Assets};
+use pfr::assets::table::{
+    art::{rescale_delays, DmPixels, TICKS_PER_SEC},
+    script::DmAnimId,
+};
+use pfr::assets::table::{dm::DmFont, physics::Layer, Assets};
 use pfr::config::TableId;
+use pfr::table::{DotMatrix, ScriptTaskDmAnim};
 use std::io::BufWriter;
 use std::{
     fs::File,
@@ -43,6 +49,182 @@ fn save_png(image: &Image, output_dir: impl AsRef<Path>, name: &str) -> std::io:
     Ok(())
 }
 
+// Stop replaying animations that go on for longer than this.
+const MAX_ANIM_TICKS: u32 = 60 * TICKS_PER_SEC;
+
+/// Plays an animation the way the script engine does, returning the distinct frames shown
+/// together with their duration in ticks.
+fn replay_dm_anim(assets: &Assets, anim: DmAnimId) -> Vec<(DmPixels, u32)> {
+    let mut dm = DotMatrix::new();
+    let mut task = ScriptTaskDmAnim::new(assets, anim);
+    let mut frames: Vec<(DmPixels, u32)> = vec![];
+    let mut ticks = 0;
+    while task.step(assets, &mut dm) {
+        match frames.last_mut() {
+            Some((pixels, duration)) if *pixels == dm.pixels => *duration += 1,
+            _ => frames.push((dm.pixels, 1)),
+        }
+        ticks += 1;
+        if ticks == MAX_ANIM_TICKS {
+            println!("ANIM {anim}: truncated after {ticks} ticks");
+            break;
+        }
+    }
+    frames
+}
+
+type DmFrames<'a> = &'a [(DmPixels, u32)];
+
+/// Splits a frame sequence into an intro and a loop that, repeated after the intro, gives
+/// the whole sequence, and the number of repeats.  Animations with a restart point play
+/// their first frames once and then loop from there.  The split with the fewest frames
+/// wins, preferring the shortest intro.
+fn split_loops(frames: DmFrames) -> (DmFrames, DmFrames, u32) {
+    let n = frames.len();
+    for len in 1..n {
+        for intro in 0..len {
+            let period = len - intro;
+            if (n - intro).is_multiple_of(period)
+                && (len..n).all(|i| frames[i] == frames[i - period])
+            {
+                return (
+                    &frames[..intro],
+                    &frames[intro..len],
+                    ((n - intro) / period) as u32,
+                );
+            }
+        }
+    }
+    (&[], frames, 1)
+}
+
+fn dm_indices(pixels: &DmPixels) -> Vec<u8> {
+    pixels.iter().flatten().map(|&x| x as u8).collect()
+}
+
+fn save_dm_anim(
+    assets: &Assets,
+    anim: DmAnimId,
+    output_dir: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let frames = replay_dm_anim(assets, anim);
+    let (intro, body, loops) = split_loops(&frames);
+    let pal = &assets.dm_palette;
+    let palette = [
+        pal.color_off.0,
+        pal.color_off.1,
+        pal.color_off.2,
+        pal.color_on.0,
+        pal.color_on.1,
+        pal.color_on.2,
+    ];
+    let ticks = |frames: DmFrames| -> u32 { frames.iter().map(|(_, duration)| duration).sum() };
+    // GIF and APNG can only loop the whole animation, so one with an intro is written out
+    // in full and played once.
+    let (frames, loops) = if intro.is_empty() {
+        println!(
+            "ANIM {anim}: {num} frames, {total_ticks} ticks, played {loops} times",
+            num = body.len(),
+            total_ticks = ticks(body),
+        );
+        (body, loops)
+    } else {
+        println!(
+            "ANIM {anim}: {num_intro} intro frames, {intro_ticks} ticks, then {num} frames, \
+             {total_ticks} ticks, played {loops} times; exported unrolled",
+            num_intro = intro.len(),
+            intro_ticks = ticks(intro),
+            num = body.len(),
+            total_ticks = ticks(body),
+        );
+        (&frames[..], 1)
+    };
+
+    let file = File::create(output_dir.as_ref().join(format!("anim{anim}.gif")))?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), 160, 16, &palette)
+        .map_err(std::io::Error::other)?;
+    if loops > 1 {
+        encoder
+            .set_repeat(gif::Repeat::Finite((loops - 1).min(0xffff) as u16))
+            .map_err(std::io::Error::other)?;
+    }
+    // GIF delays are in 1/100 s.
+    let delays = rescale_delays(frames.iter().map(|&(_, ticks)| ticks), TICKS_PER_SEC, 100);
+    for ((pixels, _), delay) in frames.iter().zip(delays) {
+        let frame = gif::Frame {
+            width: 160,
+            height: 16,
+            delay: delay.min(0xffff) as u16,
+            buffer: dm_indices(pixels).into(),
+            ..Default::default()
+        };
+        encoder.write_frame(&frame).map_err(std::io::Error::other)?;
+    }
+    drop(encoder);
+
+    let file = File::create(output_dir.as_ref().join(format!("anim{anim}.png")))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), 160, 16);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(&palette[..]);
+    encoder.set_animated(frames.len() as u32, loops)?;
+    let mut writer = encoder.write_header()?;
+    for (pixels, duration) in frames {
+        writer.set_frame_delay(*duration as u16, TICKS_PER_SEC as u16)?;
+        writer.write_image_data(&dm_indices(pixels))?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Renders every message in the given font, one per line.  Characters missing from the
+/// font and the special substitution characters are drawn as red boxes.
+fn save_msgs_sheet(
+    assets: &Assets,
+    font: DmFont,
+    output_dir: impl AsRef<Path>,
+) -> std::io::Result<()> {
+    let height = font.height() + 1;
+    let width = assets.msgs.values().map(|msg| msg.len()).max().unwrap_or(1) * 8;
+    let mut data = Array2::zeros((width, assets.msgs.len() * height));
+    for (i, msg) in assets.msgs.values().enumerate() {
+        for (j, chr) in msg.iter().enumerate() {
+            if *chr == b' ' {
+                continue;
+            }
+            match assets.dm_fonts[font].get(chr) {
+                Some(glyph) => {
+                    for y in 0..font.height() {
+                        for x in 0..8 {
+                            if glyph[y] << x & 0x80 != 0 {
+                                data[(j * 8 + x, i * height + y)] = 1;
+                            }
+                        }
+                    }
+                }
+                None => {
+                    for y in 0..font.height() {
+                        for x in 0..7 {
+                            if y == 0 || y == font.height() - 1 || x == 0 || x == 6 {
+                                data[(j * 8 + x, i * height + y)] = 2;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let image = Image {
+        data,
+        cmap: vec![
+            assets.dm_palette.color_off,
+            assets.dm_palette.color_on,
+            (0xff, 0, 0),
+        ],
+    };
+    save_png(&image, output_dir, &format!("msgs_{font:?}.png"))
+}
+
 fn main() -> std::io::Result<()> {
     let args = Args::parse();
     let (table, file) = match args.table {
@@ -164,21 +346,19 @@ fn main() -> std::io::Result<()> {
         save_png(&img, &args.output_dir, &format!("physmap_patch{idx}_d.png"))?;
     }
 
-    for (pos, uop) in &assets.scripts {
-        println!("UOP {pos}: {uop:?}");
+    for anim in assets.anims.ids() {
+        save_dm_anim(&assets, anim, &args.output_dir)?;
     }
-    for (mid, msg) in &assets.msgs {
-        let mut xmsg = String::new();
-        for &c in msg.iter() {
-            if c < 0x80 {
-                xmsg.push(c as char);
-            } else {
-                xmsg.push_str(&format!("<{c:02x}>"));
-            }
-        }
-        println!("MSG {mid}: {xmsg}");
+    for font in (0..DmFont::LENGTH).map(DmFont::from_usize) {
+        save_msgs_sheet(&assets, font, &args.output_dir)?;
     }
 
+    std::fs::write(args.output_dir.join("msgs.txt"), assets.save_msgs())?;
+    std::fs::write(
+        args.output_dir.join("scripts.scr"),
+        pfr::assets::table::asm::disassemble(&assets),
+    )?;
+
     for (i, mut flipper) in assets.flippers.values().cloned().enumerate() {
         for (j, frame) in flipper.gfx.iter().enumerate() {
             let image = Image {
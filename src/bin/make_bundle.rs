@@ -0,0 +1,28 @@
+use clap::Parser;
+use enum_map::enum_map;
+use pfr::assets::bundle::{Bundle, BUNDLE_FILE};
+use pfr::config::TableId;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Args {
+    input_dir: PathBuf,
+    output: Option<PathBuf>,
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse();
+    let intro = std::fs::read(args.input_dir.join("INTRO.PRG"))?;
+    let tables = enum_map! {
+        TableId::Table1 => std::fs::read(args.input_dir.join("TABLE1.PRG"))?,
+        TableId::Table2 => std::fs::read(args.input_dir.join("TABLE2.PRG"))?,
+        TableId::Table3 => std::fs::read(args.input_dir.join("TABLE3.PRG"))?,
+        TableId::Table4 => std::fs::read(args.input_dir.join("TABLE4.PRG"))?,
+    };
+    let bundle = Bundle::extract(&intro, &tables);
+    let output = args
+        .output
+        .unwrap_or_else(|| args.input_dir.join(BUNDLE_FILE));
+    std::fs::write(output, bundle.save())?;
+    Ok(())
+}
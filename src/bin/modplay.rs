@@ -13,7 +13,7 @@ struct Args {
 fn main() -> std::io::Result<()> {
     let args = Args::parse();
     let moddata = std::fs::read(args.modfile)?;
-    let module = pfr::sound::loader::load(&moddata);
+    let module = Arc::new(pfr::sound::loader::load(&moddata));
     let sequencer = Arc::new(TableSequencer::new(0, 0, 0, false));
     let player = pfr::sound::player::play(module, Some(sequencer.clone()));
     // println!("NAME: {}", module.name);
@@ -0,0 +1,80 @@
+//! Plays a table over and over with the autoplayer, without a window or sound, to shake out
+//! panics and stuck balls.
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    process::ExitCode,
+    sync::Arc,
+};
+
+use clap::Parser;
+use pfr::{
+    assets::table::Assets,
+    config::{Config, TableId},
+    table::{AutoplayMode, Table},
+    view::View,
+};
+
+#[derive(Parser)]
+struct Args {
+    data: PathBuf,
+    table: u8,
+    /// Stop after this many balls.
+    #[clap(long, default_value_t = 1000)]
+    balls: u64,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let (table, prg, module) = match args.table {
+        1 => (TableId::Table1, "TABLE1.PRG", "TABLE1.MOD"),
+        2 => (TableId::Table2, "TABLE2.PRG", "TABLE2.MOD"),
+        3 => (TableId::Table3, "TABLE3.PRG", "TABLE3.MOD"),
+        4 => (TableId::Table4, "TABLE4.PRG", "TABLE4.MOD"),
+        _ => {
+            eprintln!("tables are 1 to 4");
+            return ExitCode::FAILURE;
+        }
+    };
+    let read =
+        |name: &str| std::fs::read(args.data.join(name)).unwrap_or_else(|e| panic!("{name}: {e}"));
+    let assets = Arc::new(Assets::load(&read(prg), table));
+    let module = Arc::new(pfr::sound::loader::load(&read(module)));
+    let mut view = Table::from_assets_silent(assets, module, Config::default());
+    view.set_autoplay(AutoplayMode::Always);
+
+    let mut frames: u64 = 0;
+    let mut reported = 0;
+    let res = catch_unwind(AssertUnwindSafe(|| loop {
+        view.run_frame();
+        frames += 1;
+        let report = view.autoplay_report().unwrap();
+        if report.balls != reported {
+            reported = report.balls;
+            if reported % 100 == 0 {
+                println!("{reported} balls, {} games", report.games);
+            }
+        }
+        if reported >= args.balls {
+            break;
+        }
+    }));
+    let report = view.autoplay_report().unwrap();
+    println!(
+        "{} balls, {} games, {frames} frames",
+        report.balls, report.games
+    );
+    for stuck in &report.stuck {
+        println!("stuck ball: {:?} at {:?}", stuck.layer, stuck.pos);
+    }
+    if res.is_err() {
+        println!("panicked during ball {}", report.balls + 1);
+        return ExitCode::FAILURE;
+    }
+    if report.stuck.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::{Arc, OnceLock};
+
+use enum_map::EnumMap;
+
+use crate::{assets::table::Assets, config::TableId, sound::Mod};
+
+#[derive(Clone)]
+pub struct TableData {
+    pub assets: Arc<Assets>,
+    pub module: Arc<Mod>,
+}
+
+/// Keeps extracted table data around, so that going back to an already played table
+/// doesn't have to extract everything again.  Cheap to clone, all clones share the same
+/// storage.
+#[derive(Clone, Default)]
+pub struct AssetCache {
+    tables: Arc<EnumMap<TableId, OnceLock<TableData>>>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_load(&self, table: TableId, load: impl FnOnce() -> TableData) -> TableData {
+        // Each table has its own lock, so loading one doesn't hold up the others, and a table
+        // that is being preloaded in the background is waited for instead of being extracted
+        // twice.
+        self.tables[table].get_or_init(load).clone()
+    }
+
+    /// Loads all tables that aren't in the cache yet on a background thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn preload(&self, load: impl Fn(TableId) -> TableData + Send + 'static) {
+        let cache = self.clone();
+        std::thread::spawn(move || {
+            for table in [
+                TableId::Table1,
+                TableId::Table2,
+                TableId::Table3,
+                TableId::Table4,
+            ] {
+                cache.get_or_load(table, || load(table));
+            }
+        });
+    }
+}
@@ -1,13 +1,19 @@
-use std::path::PathBuf;
+use std::{cmp::Reverse, path::PathBuf};
 
-use crate::bcd::Bcd;
+use crate::{
+    assets::table::physics::{Layer, RollTrigger},
+    bcd::Bcd,
+};
 use arrayref::array_ref;
 use enum_map::{enum_map, Enum, EnumMap};
+use serde::{Deserialize, Serialize};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Config {
     pub options: Options,
-    pub high_scores: EnumMap<TableId, [HighScore; 4]>,
+    pub high_scores: EnumMap<TableId, HighScoreTable>,
+    pub practice_shots: EnumMap<TableId, [Option<PracticeShot>; PRACTICE_SLOTS]>,
+    pub stats: EnumMap<TableId, TableStats>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -18,12 +24,137 @@ pub struct Options {
     pub resolution: Resolution,
     pub no_music: bool,
     pub mono: bool,
+    /// In percent, from 50 to 100.  Slows down everything: the ball, the flippers, the
+    /// timers and the music.
+    pub game_speed: u8,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct HighScore {
     pub score: Bcd,
     pub name: [u8; 3],
+    /// Unknown for scores from the DOS `.HI` files.
+    pub details: Option<HighScoreDetails>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HighScoreDetails {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub balls: u8,
+    pub angle_high: bool,
+    pub players: u8,
+}
+
+pub const MAX_HIGH_SCORES: usize = 50;
+
+/// A table's high scores, best first.  There are always at least four, and the first four
+/// are the ones stored in the DOS `.HI` files.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct HighScoreTable {
+    entries: [HighScore; MAX_HIGH_SCORES],
+    len: usize,
+}
+
+impl HighScoreTable {
+    pub fn new(top: [HighScore; 4]) -> Self {
+        let mut entries = [top[3]; MAX_HIGH_SCORES];
+        entries[..4].copy_from_slice(&top);
+        Self { entries, len: 4 }
+    }
+
+    pub fn top(&self) -> [HighScore; 4] {
+        *array_ref![self.entries, 0, 4]
+    }
+
+    /// Returns the place a new score would get, if any.
+    pub fn place(&self, score: Bcd) -> Option<usize> {
+        match self.iter().position(|entry| score > entry.score) {
+            Some(place) => Some(place),
+            None if self.len < MAX_HIGH_SCORES && score != Bcd::ZERO => Some(self.len),
+            None => None,
+        }
+    }
+
+    pub fn insert(&mut self, place: usize, entry: HighScore) {
+        if place >= MAX_HIGH_SCORES {
+            return;
+        }
+        self.len = (self.len + 1).min(MAX_HIGH_SCORES);
+        self.entries.copy_within(place..self.len - 1, place + 1);
+        self.entries[place] = entry;
+    }
+}
+
+impl std::ops::Deref for HighScoreTable {
+    type Target = [HighScore];
+
+    fn deref(&self) -> &[HighScore] {
+        &self.entries[..self.len]
+    }
+}
+
+pub const PRACTICE_SLOTS: usize = 9;
+
+/// A ball start position saved in practice mode.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PracticeShot {
+    /// Space padded.
+    pub name: [u8; 8],
+    pub layer: Layer,
+    pub pos: (i16, i16),
+    pub speed: (i16, i16),
+}
+
+/// Lifetime statistics of a table, summed over all players of all finished games.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct TableStats {
+    pub games: u32,
+    pub total_score: u64,
+    pub best_score: Bcd,
+    pub balls_drained: u32,
+    pub extra_balls: u32,
+    pub tilts: u32,
+    pub jackpots: u32,
+    pub modes_started: u32,
+    pub modes_completed: u32,
+    pub match_wins: u32,
+    /// Indexed like [`stat_rolls`].
+    pub rolls: [u32; 6],
+}
+
+/// The ramp and orbit roll triggers counted in [`TableStats::rolls`].
+pub fn stat_rolls(table: TableId) -> &'static [RollTrigger] {
+    match table {
+        TableId::Table1 => &[
+            RollTrigger::PartyRampCyclone,
+            RollTrigger::PartyRampSnack,
+            RollTrigger::PartySkyrideRamp,
+            RollTrigger::PartyOrbitTopLeft,
+            RollTrigger::PartyOrbitTopRight,
+        ],
+        TableId::Table2 => &[
+            RollTrigger::SpeedRampOffroad,
+            RollTrigger::SpeedRampMilesLeft,
+            RollTrigger::SpeedRampMilesRight,
+            RollTrigger::SpeedPitLoopJump,
+        ],
+        TableId::Table3 => &[
+            RollTrigger::ShowRampTop,
+            RollTrigger::ShowRampSkill,
+            RollTrigger::ShowRampRight,
+            RollTrigger::ShowRampLoop,
+            RollTrigger::ShowOrbitLeft,
+            RollTrigger::ShowOrbitRight,
+        ],
+        TableId::Table4 => &[
+            RollTrigger::StonesRampTower,
+            RollTrigger::StonesRampTop,
+            RollTrigger::StonesRampScreams,
+            RollTrigger::StonesRampLeftToLane,
+            RollTrigger::StonesRampLeftToVault,
+        ],
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
@@ -56,7 +187,7 @@ pub enum Resolution {
     Full,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Enum, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Enum, Debug, Serialize, Deserialize)]
 pub enum TableId {
     Table1,
     Table2,
@@ -73,6 +204,7 @@ impl Default for Options {
             resolution: Resolution::Normal,
             no_music: false,
             mono: false,
+            game_speed: 100,
         }
     }
 }
@@ -82,32 +214,34 @@ impl Default for Config {
         Self {
             options: Default::default(),
             high_scores: enum_map! {
-                TableId::Table1 => [
-                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"50000000") },
-                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"25000000") },
-                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"10000000") },
-                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"5000000") },
-                ],
-                TableId::Table2 => [
-                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"100000000") },
-                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"50000000") },
-                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"25000000") },
-                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"10000000") },
-                ],
-                TableId::Table3 => [
-                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"50000000") },
-                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"25000000") },
-                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"10000000") },
-                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"5000000") },
-
-                ],
-                TableId::Table4 => [
-                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"100000000") },
-                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"50000000") },
-                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"25000000") },
-                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"10000000") },
-                ],
+                TableId::Table1 => HighScoreTable::new([
+                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"50000000"), details: None },
+                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"25000000"), details: None },
+                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"10000000"), details: None },
+                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"5000000"), details: None },
+                ]),
+                TableId::Table2 => HighScoreTable::new([
+                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"100000000"), details: None },
+                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"50000000"), details: None },
+                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"25000000"), details: None },
+                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"10000000"), details: None },
+                ]),
+                TableId::Table3 => HighScoreTable::new([
+                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"50000000"), details: None },
+                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"25000000"), details: None },
+                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"10000000"), details: None },
+                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"5000000"), details: None },
+
+                ]),
+                TableId::Table4 => HighScoreTable::new([
+                    HighScore { name: *b"TSP", score: Bcd::from_ascii(b"100000000"), details: None },
+                    HighScore { name: *b"ICE", score: Bcd::from_ascii(b"50000000"), details: None },
+                    HighScore { name: *b"ANY", score: Bcd::from_ascii(b"25000000"), details: None },
+                    HighScore { name: *b"J L", score: Bcd::from_ascii(b"10000000"), details: None },
+                ]),
             },
+            practice_shots: EnumMap::default(),
+            stats: EnumMap::default(),
         }
     }
 }
@@ -136,28 +270,219 @@ impl Config {
                 res.options.mono = cfg[5] == 1;
             }
         }
+        if let Some(cfx) = store.load("PINBALL.CFX") {
+            parse_options_ext(&cfx, &mut res.options);
+        }
         for (table, file) in [
             (TableId::Table1, "TABLE1.HI"),
             (TableId::Table2, "TABLE2.HI"),
             (TableId::Table3, "TABLE3.HI"),
             (TableId::Table4, "TABLE4.HI"),
         ] {
+            let mut top = res.high_scores[table].top();
             if let Some(hi) = store.load(file) {
                 if hi.len() == 0x40 {
-                    for i in 0..4 {
+                    for (i, score) in top.iter_mut().enumerate() {
                         let pos = i * 0x10;
                         let entry = &hi[pos..pos + 0x10];
-                        res.high_scores[table][i].score =
-                            Bcd::from_bytes(*array_ref![entry, 0, 12]);
-                        res.high_scores[table][i].name = *array_ref![entry, 12, 3];
+                        score.score = Bcd::from_bytes(*array_ref![entry, 0, 12]);
+                        score.name = *array_ref![entry, 12, 3];
                     }
                 }
             }
+            res.high_scores[table] = HighScoreTable::new(top);
+        }
+        for (table, file) in [
+            (TableId::Table1, "TABLE1.HSX"),
+            (TableId::Table2, "TABLE2.HSX"),
+            (TableId::Table3, "TABLE3.HSX"),
+            (TableId::Table4, "TABLE4.HSX"),
+        ] {
+            if let Some(hsx) = store.load(file) {
+                let top = res.high_scores[table].top();
+                res.high_scores[table] = parse_high_scores(&hsx, top);
+            }
+        }
+        for (table, file) in [
+            (TableId::Table1, "TABLE1.PRC"),
+            (TableId::Table2, "TABLE2.PRC"),
+            (TableId::Table3, "TABLE3.PRC"),
+            (TableId::Table4, "TABLE4.PRC"),
+        ] {
+            if let Some(prc) = store.load(file) {
+                res.practice_shots[table] = parse_practice_shots(&prc);
+            }
+        }
+        for (table, file) in [
+            (TableId::Table1, "TABLE1.STA"),
+            (TableId::Table2, "TABLE2.STA"),
+            (TableId::Table3, "TABLE3.STA"),
+            (TableId::Table4, "TABLE4.STA"),
+        ] {
+            if let Some(sta) = store.load(file) {
+                res.stats[table] = parse_stats(table, &sta);
+            }
         }
         res
     }
 }
 
+/// Options the DOS version doesn't have, one `<name> <value>` line each.
+fn parse_options_ext(data: &[u8], options: &mut Options) {
+    for line in String::from_utf8_lossy(data).lines() {
+        let Some((name, value)) = line.split_once(' ') else {
+            continue;
+        };
+        if name == "game_speed" {
+            if let Ok(speed @ 50..=100) = value.trim().parse() {
+                options.game_speed = speed;
+            }
+        }
+    }
+}
+
+/// The extended high scores are stored as text, one
+/// `<score> <time> <balls> <angle> <players> <name>` line per entry, best first.  The details
+/// are `-` when unknown.  Scores from the DOS `.HI` file that are missing from the list were
+/// made with the DOS version, and are merged in.
+fn parse_high_scores(data: &[u8], top: [HighScore; 4]) -> HighScoreTable {
+    let mut entries = vec![];
+    for line in String::from_utf8_lossy(data).lines() {
+        let fields: Vec<_> = line.splitn(6, ' ').collect();
+        let [score, time, balls, angle, players, name] = fields[..] else {
+            continue;
+        };
+        if score.is_empty()
+            || score.len() > Bcd::DIGITS
+            || !score.bytes().all(|c| c.is_ascii_digit())
+            || name.len() != 3
+        {
+            continue;
+        }
+        let details = match (time.parse(), balls.parse(), angle, players.parse()) {
+            (Ok(time), Ok(balls), "high" | "low", Ok(players)) => Some(HighScoreDetails {
+                time,
+                balls,
+                angle_high: angle == "high",
+                players,
+            }),
+            _ => None,
+        };
+        entries.push(HighScore {
+            score: Bcd::from_ascii(score.as_bytes()),
+            name: *array_ref![name.as_bytes(), 0, 3],
+            details,
+        });
+    }
+    entries.sort_by_key(|entry| Reverse(entry.score));
+    entries.truncate(MAX_HIGH_SCORES);
+    for entry in top {
+        if !entries
+            .iter()
+            .any(|e| e.score == entry.score && e.name == entry.name)
+        {
+            let place = entries
+                .iter()
+                .position(|e| entry.score > e.score)
+                .unwrap_or(entries.len());
+            entries.insert(place, entry);
+        }
+    }
+    entries.truncate(MAX_HIGH_SCORES);
+    if entries.len() < 4 {
+        return HighScoreTable::new(top);
+    }
+    let mut res = HighScoreTable::new(*array_ref![entries, 0, 4]);
+    for entry in &entries[4..] {
+        res.insert(res.len, *entry);
+    }
+    res
+}
+
+/// Practice shots are stored as text, one `<slot> <layer> <x> <y> <speed x> <speed y> <name>`
+/// line per shot.  Malformed lines are ignored.
+fn parse_practice_shots(data: &[u8]) -> [Option<PracticeShot>; PRACTICE_SLOTS] {
+    let mut res = [None; PRACTICE_SLOTS];
+    for line in String::from_utf8_lossy(data).lines() {
+        let fields: Vec<_> = line.splitn(7, ' ').collect();
+        let [slot, layer, x, y, vx, vy, name] = fields[..] else {
+            continue;
+        };
+        let layer = match layer {
+            "G" => Layer::Ground,
+            "O" => Layer::Overhead,
+            _ => continue,
+        };
+        let (Ok(slot), Ok(x), Ok(y), Ok(vx), Ok(vy)) = (
+            slot.parse::<usize>(),
+            x.parse(),
+            y.parse(),
+            vx.parse(),
+            vy.parse(),
+        ) else {
+            continue;
+        };
+        if !(1..=PRACTICE_SLOTS).contains(&slot) {
+            continue;
+        }
+        let mut padded = [b' '; 8];
+        for (dst, src) in padded.iter_mut().zip(name.bytes()) {
+            *dst = src.to_ascii_uppercase();
+        }
+        res[slot - 1] = Some(PracticeShot {
+            name: padded,
+            layer,
+            pos: (x, y),
+            speed: (vx, vy),
+        });
+    }
+    res
+}
+
+/// Statistics are stored as text, one `<name> <value>` line per counter.  Roll triggers use
+/// their names prefixed with `roll.`.  Unknown or malformed lines are ignored.
+fn parse_stats(table: TableId, data: &[u8]) -> TableStats {
+    let mut res = TableStats::default();
+    for line in String::from_utf8_lossy(data).lines() {
+        let Some((name, value)) = line.split_once(' ') else {
+            continue;
+        };
+        if name == "best_score" {
+            if value.len() <= Bcd::DIGITS && value.bytes().all(|c| c.is_ascii_digit()) {
+                res.best_score = Bcd::from_ascii(value.as_bytes());
+            }
+            continue;
+        }
+        if name == "total_score" {
+            res.total_score = value.parse().unwrap_or(0);
+            continue;
+        }
+        let Ok(value) = value.parse() else {
+            continue;
+        };
+        let field = match name {
+            "games" => &mut res.games,
+            "balls_drained" => &mut res.balls_drained,
+            "extra_balls" => &mut res.extra_balls,
+            "tilts" => &mut res.tilts,
+            "jackpots" => &mut res.jackpots,
+            "modes_started" => &mut res.modes_started,
+            "modes_completed" => &mut res.modes_completed,
+            "match_wins" => &mut res.match_wins,
+            _ => {
+                let Some(idx) = stat_rolls(table).iter().position(|kind| {
+                    Some(format!("{kind:?}").as_str()) == name.strip_prefix("roll.")
+                }) else {
+                    continue;
+                };
+                &mut res.rolls[idx]
+            }
+        };
+        *field = value;
+    }
+    res
+}
+
 impl Options {
     pub fn save(&self, store: &impl ConfigStore) {
         let raw: [u8; 6] = [
@@ -177,23 +502,115 @@ impl Options {
             u8::from(self.mono),
         ];
         store.save("PINBALL.CFG", &raw);
+        store.save(
+            "PINBALL.CFX",
+            format!("game_speed {}\n", self.game_speed).as_bytes(),
+        );
     }
 }
 
-pub fn save_high_scores(table: TableId, scores: [HighScore; 4], store: &impl ConfigStore) {
-    let file = match table {
-        TableId::Table1 => "TABLE1.HI",
-        TableId::Table2 => "TABLE2.HI",
-        TableId::Table3 => "TABLE3.HI",
-        TableId::Table4 => "TABLE4.HI",
+/// Saves the full list, and the first four entries in the DOS format.
+pub fn save_high_scores(table: TableId, scores: &HighScoreTable, store: &impl ConfigStore) {
+    let (file, file_ext) = match table {
+        TableId::Table1 => ("TABLE1.HI", "TABLE1.HSX"),
+        TableId::Table2 => ("TABLE2.HI", "TABLE2.HSX"),
+        TableId::Table3 => ("TABLE3.HI", "TABLE3.HSX"),
+        TableId::Table4 => ("TABLE4.HI", "TABLE4.HSX"),
     };
     let mut raw = vec![];
-    for score in scores {
+    for score in scores.top() {
         raw.extend(score.score.digits);
         raw.extend(score.name);
         raw.push(0);
     }
     store.save(file, &raw);
+    let mut raw = String::new();
+    for score in scores.iter() {
+        raw += String::from_utf8_lossy(&score.score.to_ascii()).trim_start();
+        match score.details {
+            Some(details) => {
+                raw += &format!(
+                    " {time} {balls} {angle} {players}",
+                    time = details.time,
+                    balls = details.balls,
+                    angle = if details.angle_high { "high" } else { "low" },
+                    players = details.players,
+                );
+            }
+            None => raw += " - - - -",
+        }
+        raw += &format!(" {name}\n", name = String::from_utf8_lossy(&score.name));
+    }
+    store.save(file_ext, raw.as_bytes());
+}
+
+pub fn save_practice_shots(
+    table: TableId,
+    shots: [Option<PracticeShot>; PRACTICE_SLOTS],
+    store: &impl ConfigStore,
+) {
+    let file = match table {
+        TableId::Table1 => "TABLE1.PRC",
+        TableId::Table2 => "TABLE2.PRC",
+        TableId::Table3 => "TABLE3.PRC",
+        TableId::Table4 => "TABLE4.PRC",
+    };
+    let mut raw = String::new();
+    for (slot, shot) in shots.iter().enumerate() {
+        if let Some(shot) = shot {
+            raw += &format!(
+                "{slot} {layer} {x} {y} {vx} {vy} {name}\n",
+                slot = slot + 1,
+                layer = match shot.layer {
+                    Layer::Ground => "G",
+                    Layer::Overhead => "O",
+                },
+                x = shot.pos.0,
+                y = shot.pos.1,
+                vx = shot.speed.0,
+                vy = shot.speed.1,
+                name = String::from_utf8_lossy(&shot.name).trim_end(),
+            );
+        }
+    }
+    store.save(file, raw.as_bytes());
+}
+
+pub fn save_stats(table: TableId, stats: TableStats, store: &impl ConfigStore) {
+    let file = match table {
+        TableId::Table1 => "TABLE1.STA",
+        TableId::Table2 => "TABLE2.STA",
+        TableId::Table3 => "TABLE3.STA",
+        TableId::Table4 => "TABLE4.STA",
+    };
+    let best_score = String::from_utf8_lossy(&stats.best_score.to_ascii())
+        .trim_start()
+        .to_string();
+    let mut raw = format!(
+        "games {games}\n\
+        total_score {total_score}\n\
+        best_score {best_score}\n\
+        balls_drained {balls_drained}\n\
+        extra_balls {extra_balls}\n\
+        tilts {tilts}\n\
+        jackpots {jackpots}\n\
+        modes_started {modes_started}\n\
+        modes_completed {modes_completed}\n\
+        match_wins {match_wins}\n",
+        games = stats.games,
+        total_score = stats.total_score,
+        balls_drained = stats.balls_drained,
+        extra_balls = stats.extra_balls,
+        tilts = stats.tilts,
+        jackpots = stats.jackpots,
+        modes_started = stats.modes_started,
+        modes_completed = stats.modes_completed,
+        match_wins = stats.match_wins,
+    );
+    for (kind, count) in stat_rolls(table).iter().zip(stats.rolls) {
+        raw += &format!("roll.{kind:?} {count}\n");
+    }
+    store.save(file, raw.as_bytes());
 }
 
 pub trait ConfigStore {
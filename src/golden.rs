@@ -0,0 +1,351 @@
+//! Golden frame tests.  Each test plays the intro or a table to a known frame, renders it, and
+//! compares the picture with a PNG stored in the golden directory.  When they differ, the new
+//! frame and a diff are written to `target/golden/`: the diff has the golden frame on the left,
+//! the new one in the middle, and on the right the golden frame dimmed, with every changed
+//! pixel in red.
+//!
+//! The golden frames are pictures of the game data, so they aren't part of the repository.
+//! They live in the directory given in `PFR_GOLDEN`, and are created by running the tests
+//! once with `PFR_BLESS=1` on a build whose output is known to be right.  `PFR_BLESS=1` also
+//! accepts an intended change.  A missing golden frame is a failure.  Like the scenario
+//! tests, these need the game data and are ignored by default:
+//!
+//! ```text
+//! PFR_BLESS=1 PFR_GOLDEN=<golden dir> PFR_DATA=<game directory> cargo test golden -- --ignored
+//! PFR_GOLDEN=<golden dir> PFR_DATA=<game directory> cargo test golden -- --ignored
+//! ```
+
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::{
+    assets::{
+        intro::Assets,
+        table::{
+            lights::LightBind,
+            physics::RollTrigger,
+            script::{EffectBind, ScriptBind},
+        },
+    },
+    config::{Config, Resolution, TableId},
+    intro::Intro,
+    table::{
+        scenario::{data_dir, table_data, Scenario},
+        Table,
+    },
+    view::View,
+};
+
+struct Frame {
+    width: usize,
+    height: usize,
+    /// Row by row, three bytes per pixel.
+    rgb: Vec<u8>,
+}
+
+impl Frame {
+    fn render(view: &dyn View) -> Self {
+        let (width, height) = view.get_resolution();
+        let (width, height) = (width as usize, height as usize);
+        let mut data = vec![0; width * height];
+        let mut pal = [(0, 0, 0); 256];
+        view.render(&mut data, &mut pal);
+        let rgb = data
+            .iter()
+            .flat_map(|&pixel| {
+                let (r, g, b) = pal[pixel as usize];
+                [r, g, b]
+            })
+            .collect();
+        Frame { width, height, rgb }
+    }
+
+    fn load(path: &PathBuf) -> Self {
+        let file = File::open(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        let mut decoder = png::Decoder::new(file);
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Rgb, "{}", path.display());
+        data.truncate(info.buffer_size());
+        Frame {
+            width: info.width as usize,
+            height: info.height as usize,
+            rgb: data,
+        }
+    }
+
+    fn save(&self, path: &PathBuf) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let file = File::create(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.rgb))
+            .unwrap();
+    }
+
+    /// The golden frame, this one and the changes, side by side.  Returns `None` if nothing
+    /// changed.
+    fn diff(&self, golden: &Frame) -> Option<(Frame, usize)> {
+        let mut res = Frame {
+            width: self.width * 3,
+            height: self.height,
+            rgb: vec![0; self.width * 3 * self.height * 3],
+        };
+        let mut changed = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let src = (y * self.width + x) * 3;
+                let old = &golden.rgb[src..src + 3];
+                let new = &self.rgb[src..src + 3];
+                let dst = (y * res.width + x) * 3;
+                res.rgb[dst..dst + 3].copy_from_slice(old);
+                res.rgb[dst + self.width * 3..dst + self.width * 3 + 3].copy_from_slice(new);
+                let mark = if old == new {
+                    let luma = (old[0] as u32 * 299 + old[1] as u32 * 587 + old[2] as u32 * 114)
+                        / 1000
+                        / 3;
+                    [luma as u8; 3]
+                } else {
+                    changed += 1;
+                    [0xff, 0, 0]
+                };
+                res.rgb[dst + self.width * 6..dst + self.width * 6 + 3].copy_from_slice(&mark);
+            }
+        }
+        (changed != 0).then_some((res, changed))
+    }
+}
+
+/// The directory with the golden frames, from `PFR_GOLDEN`.
+fn golden_dir() -> PathBuf {
+    std::env::var_os("PFR_GOLDEN")
+        .unwrap_or_else(|| {
+            panic!("PFR_GOLDEN isn't set; run once with PFR_BLESS=1 to fill the directory")
+        })
+        .into()
+}
+
+fn check(name: &str, view: &dyn View) {
+    let frame = Frame::render(view);
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let path = golden_dir().join(format!("{name}.png"));
+    if std::env::var_os("PFR_BLESS").is_some_and(|bless| bless == "1") {
+        eprintln!("writing golden frame {}", path.display());
+        frame.save(&path);
+        return;
+    }
+    let out = root.join("target").join("golden");
+    if !path.exists() {
+        frame.save(&out.join(format!("{name}.png")));
+        panic!(
+            "{name}: no golden frame {}, run with PFR_BLESS=1 to write it",
+            path.display()
+        );
+    }
+    let golden = Frame::load(&path);
+    if (golden.width, golden.height) != (frame.width, frame.height) {
+        frame.save(&out.join(format!("{name}.png")));
+        panic!(
+            "{name}: frame is {}×{}, golden frame is {}×{}",
+            frame.width, frame.height, golden.width, golden.height
+        );
+    }
+    if let Some((diff, changed)) = frame.diff(&golden) {
+        frame.save(&out.join(format!("{name}.png")));
+        let diff_path = out.join(format!("{name}.diff.png"));
+        diff.save(&diff_path);
+        panic!(
+            "{name}: {changed} pixels changed, see {}",
+            diff_path.display()
+        );
+    }
+}
+
+fn run(view: &mut dyn View, frames: u32) {
+    for _ in 0..frames {
+        view.run_frame();
+    }
+}
+
+fn press(view: &mut dyn View, key: KeyCode) {
+    view.handle_key(key, ElementState::Pressed);
+    view.handle_key(key, ElementState::Released);
+}
+
+/// The intro, starting with the slides if `table` is `None`, or at the table menu after
+/// coming back from a table.
+fn intro(table: Option<TableId>, resolution: Resolution) -> Intro {
+    let dir = data_dir();
+    let read = |name: &str| std::fs::read(dir.join(name)).unwrap_or_else(|e| panic!("{name}: {e}"));
+    let module = if table.is_none() {
+        "INTRO.MOD"
+    } else {
+        "MOD2.MOD"
+    };
+    let mut config = Config::default();
+    config.options.resolution = resolution;
+    Intro::from_assets_silent(
+        Assets::load(&read("INTRO.PRG")),
+        &read(module),
+        config,
+        table,
+    )
+}
+
+fn table(table: TableId, resolution: Resolution) -> Table {
+    let data = table_data(table);
+    let mut config = Config::default();
+    config.options.resolution = resolution;
+    let mut view = Table::from_assets_silent(data.assets, data.module, config);
+    view.seed(0);
+    view.set_keep_records(false);
+    view
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn intro_slides() {
+    let mut view = intro(None, Resolution::Normal);
+    // The slides are timed by the music, so this also catches the music falling behind.
+    let mut done = 0;
+    for frame in [150, 750] {
+        run(&mut view, frame - done);
+        done = frame;
+        check(&format!("intro_slides_{frame}"), &view);
+    }
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn intro_menus() {
+    for (resolution, suffix) in [(Resolution::Normal, ""), (Resolution::Full, "_vertical")] {
+        let mut view = intro(Some(TableId::Table1), resolution);
+        run(&mut view, 200);
+        check(&format!("intro_tables{suffix}"), &view);
+        press(&mut view, KeyCode::Space);
+        run(&mut view, 200);
+        check(&format!("intro_text{suffix}"), &view);
+        press(&mut view, KeyCode::F5);
+        run(&mut view, 200);
+        check(&format!("intro_options{suffix}"), &view);
+        press(&mut view, KeyCode::ArrowDown);
+        run(&mut view, 1);
+        check(&format!("intro_options_cursor{suffix}"), &view);
+    }
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn intro_second_text_page() {
+    let mut view = intro(Some(TableId::Table3), Resolution::Normal);
+    run(&mut view, 200);
+    press(&mut view, KeyCode::Space);
+    run(&mut view, 200);
+    check("intro_text_1", &view);
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn table_attract() {
+    for (id, name) in [
+        (TableId::Table1, "party"),
+        (TableId::Table2, "speed"),
+        (TableId::Table3, "show"),
+        (TableId::Table4, "stones"),
+    ] {
+        for (resolution, suffix) in [
+            (Resolution::Normal, "normal"),
+            (Resolution::High, "high"),
+            (Resolution::Full, "full"),
+        ] {
+            let mut view = table(id, resolution);
+            run(&mut view, 120);
+            check(&format!("{name}_attract_{suffix}"), &view);
+        }
+    }
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn table_game() {
+    for (id, name) in [
+        (TableId::Table1, "party"),
+        (TableId::Table2, "speed"),
+        (TableId::Table3, "show"),
+        (TableId::Table4, "stones"),
+    ] {
+        let mut view = table(id, Resolution::Normal);
+        press(&mut view, KeyCode::F1);
+        // The dot matrix shows the player and ball.
+        run(&mut view, 120);
+        check(&format!("{name}_game_start"), &view);
+        press(&mut view, KeyCode::KeyP);
+        run(&mut view, 60);
+        check(&format!("{name}_paused"), &view);
+        // Options changed while paused are shown on the dot matrix.
+        press(&mut view, KeyCode::KeyG);
+        run(&mut view, 1);
+        check(&format!("{name}_paused_option"), &view);
+    }
+}
+
+#[test]
+#[ignore = "needs the game data in PFR_DATA"]
+fn table_messages() {
+    // A mode started by the rules, partway through its dot matrix message.
+    let mut s = Scenario::start(TableId::Table1);
+    s.set_lights(LightBind::PartyParty, true);
+    s.set_light(LightBind::PartyParty, 4, false);
+    s.set_lights(LightBind::PartyPuke, true);
+    s.set_light(LightBind::PartyPuke, 3, false);
+    s.roll(RollTrigger::PartySkyridePuke(3));
+    s.run(30);
+    check("party_happy_hour", &s.table);
+
+    let mut s = Scenario::start(TableId::Table1);
+    s.table.start_script(ScriptBind::PartyJackpot);
+    s.run(30);
+    check("party_jackpot", &s.table);
+    for (id, effect, name) in [
+        (TableId::Table2, EffectBind::SpeedJackpot, "speed_jackpot"),
+        (TableId::Table3, EffectBind::ShowJackpot, "show_jackpot"),
+        (
+            TableId::Table4,
+            EffectBind::StonesTowerJackpot,
+            "stones_jackpot",
+        ),
+    ] {
+        let mut s = Scenario::start(id);
+        s.table.effect(effect);
+        s.run(30);
+        check(name, &s.table);
+    }
+}
+
+#[test]
+fn diff() {
+    let golden = Frame {
+        width: 2,
+        height: 1,
+        rgb: vec![30, 30, 30, 0, 0, 0],
+    };
+    assert!(golden.diff(&golden).is_none());
+    let frame = Frame {
+        width: 2,
+        height: 1,
+        rgb: vec![30, 30, 30, 9, 9, 9],
+    };
+    let (diff, changed) = frame.diff(&golden).unwrap();
+    assert_eq!(changed, 1);
+    assert_eq!(
+        diff.rgb,
+        [30, 30, 30, 0, 0, 0, 30, 30, 30, 9, 9, 9, 10, 10, 10, 0xff, 0, 0]
+    );
+}
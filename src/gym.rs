@@ -0,0 +1,265 @@
+//! A reinforcement learning environment, in the style of Gym.  [`Env::reset`] starts a
+//! one-player game on a fresh table, and every [`Env::step`] holds the given buttons for a
+//! few frames, then reports what the table looks like and how many points were scored.
+//!
+//! There's no window, sound or frame pacing, so games run as fast as the physics allow.  The
+//! screen is only drawn for frame observations, and only for the last frame of each step.
+
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::{
+    assets::table::{flippers::FlipperSide, physics::Layer},
+    cache::{AssetCache, TableData},
+    config::{Config, TableId},
+    table::Table,
+    view::View,
+};
+
+// A game that hasn't started after this long never will.
+const START_FRAMES: u32 = 60 * 10;
+
+/// The buttons held down during a step.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Action {
+    pub flipper_left: bool,
+    pub flipper_right: bool,
+    pub nudge: bool,
+    pub plunger: bool,
+}
+
+impl Action {
+    /// The number of different actions, for agents with a discrete action space.
+    pub const COUNT: usize = 16;
+
+    /// Action number `idx`, with one bit per button: the left flipper, the right flipper, the
+    /// nudge and the plunger, from the lowest.
+    pub fn from_index(idx: usize) -> Self {
+        assert!(idx < Self::COUNT);
+        Self {
+            flipper_left: idx & 1 != 0,
+            flipper_right: idx & 2 != 0,
+            nudge: idx & 4 != 0,
+            plunger: idx & 8 != 0,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self.flipper_left as usize
+            | (self.flipper_right as usize) << 1
+            | (self.nudge as usize) << 2
+            | (self.plunger as usize) << 3
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ObservationKind {
+    /// The ball, the flippers and the lights, see [`TableState`].
+    State,
+    /// The screen in grayscale, shrunk by averaging blocks of `downscale`×`downscale` pixels.
+    Frame { downscale: u8 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableState {
+    pub ball_layer: Layer,
+    /// The ball's center, in table pixels.
+    pub ball_pos: (i16, i16),
+    /// In 1/1024 pixels per physics step.
+    pub ball_speed: (i16, i16),
+    /// For every flipper, 0 at rest, counting up as it is raised.
+    pub flippers: Vec<(FlipperSide, u16)>,
+    /// Whether each light is lit, by light number.
+    pub lights: Vec<bool>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Observation {
+    State(TableState),
+    Frame {
+        width: usize,
+        height: usize,
+        /// Row by row, one byte per pixel.
+        pixels: Vec<u8>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub observation: Observation,
+    /// Points scored during the step.
+    pub reward: u64,
+    /// Set once the game is over.
+    pub done: bool,
+}
+
+pub struct Env {
+    load: Box<dyn FnMut(TableId) -> TableData>,
+    cache: AssetCache,
+    config: Config,
+    observation: ObservationKind,
+    frame_skip: u32,
+    table: Option<Table>,
+    action: Action,
+    score: u64,
+}
+
+impl Env {
+    /// `load` extracts a table's data the first time it is played; later games reuse it.
+    pub fn new(
+        config: Config,
+        observation: ObservationKind,
+        load: impl FnMut(TableId) -> TableData + 'static,
+    ) -> Self {
+        Self {
+            load: Box::new(load),
+            cache: AssetCache::new(),
+            config,
+            observation,
+            frame_skip: 1,
+            table: None,
+            action: Action::default(),
+            score: 0,
+        }
+    }
+
+    /// Sets how many frames each step runs for, 1 by default.  Only the last frame of a step
+    /// is observed, the others aren't drawn at all.
+    pub fn set_frame_skip(&mut self, frames: u32) {
+        assert!(frames != 0);
+        self.frame_skip = frames;
+    }
+
+    /// Starts a new game.  The same seed and the same actions make for the same game.
+    pub fn reset(&mut self, table: TableId, seed: u64) -> Observation {
+        let data = self.cache.get_or_load(table, || (self.load)(table));
+        let mut view = Table::from_assets_silent(data.assets, data.module, self.config);
+        view.seed(seed);
+        view.set_keep_records(false);
+        view.handle_key(KeyCode::F1, ElementState::Pressed);
+        view.handle_key(KeyCode::F1, ElementState::Released);
+        for _ in 0..START_FRAMES {
+            view.run_frame();
+            if !view.info().in_attract {
+                break;
+            }
+        }
+        assert!(!view.info().in_attract, "game didn't start");
+        self.table = Some(view);
+        self.action = Action::default();
+        self.score = 0;
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: Action) -> Step {
+        let view = self.table.as_mut().expect("step before reset");
+        for (key, old, new) in [
+            (
+                KeyCode::ShiftLeft,
+                self.action.flipper_left,
+                action.flipper_left,
+            ),
+            (
+                KeyCode::ShiftRight,
+                self.action.flipper_right,
+                action.flipper_right,
+            ),
+            (KeyCode::Space, self.action.nudge, action.nudge),
+            (KeyCode::ArrowDown, self.action.plunger, action.plunger),
+        ] {
+            if old != new {
+                view.handle_key(
+                    key,
+                    if new {
+                        ElementState::Pressed
+                    } else {
+                        ElementState::Released
+                    },
+                );
+            }
+        }
+        self.action = action;
+        let mut done = view.info().in_attract;
+        for _ in 0..self.frame_skip {
+            if done {
+                break;
+            }
+            view.run_frame();
+            done = view.info().in_attract;
+        }
+        let score = view.info().scores.first().copied().unwrap_or(0);
+        let reward = score.saturating_sub(self.score);
+        self.score = score;
+        Step {
+            observation: self.observe(),
+            reward,
+            done,
+        }
+    }
+
+    fn observe(&self) -> Observation {
+        let view = self.table.as_ref().unwrap();
+        match self.observation {
+            ObservationKind::State => {
+                let ball = view.info().ball;
+                Observation::State(TableState {
+                    ball_layer: ball.layer,
+                    ball_pos: (
+                        (ball.pos_hires.0 >> 10) as i16 + 8,
+                        (ball.pos_hires.1 >> 10) as i16 + 8,
+                    ),
+                    ball_speed: ball.speed,
+                    flippers: view.flipper_quantums(),
+                    lights: view.lights_lit(),
+                })
+            }
+            ObservationKind::Frame { downscale } => {
+                let (width, height) = view.get_resolution();
+                let (width, height) = (width as usize, height as usize);
+                let mut data = vec![0; width * height];
+                let mut pal = [(0, 0, 0); 256];
+                view.render(&mut data, &mut pal);
+                let luma =
+                    pal.map(|(r, g, b)| (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000);
+                let scale = downscale.max(1) as usize;
+                let (out_width, out_height) = (width / scale, height / scale);
+                let mut pixels = Vec::with_capacity(out_width * out_height);
+                for y in 0..out_height {
+                    for x in 0..out_width {
+                        let mut sum = 0;
+                        for sy in y * scale..(y + 1) * scale {
+                            for sx in x * scale..(x + 1) * scale {
+                                sum += luma[data[sy * width + sx] as usize];
+                            }
+                        }
+                        pixels.push((sum / (scale * scale) as u32) as u8);
+                    }
+                }
+                Observation::Frame {
+                    width: out_width,
+                    height: out_height,
+                    pixels,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions() {
+        for idx in 0..Action::COUNT {
+            assert_eq!(Action::from_index(idx).index(), idx);
+        }
+        assert_eq!(
+            Action::from_index(9),
+            Action {
+                flipper_left: true,
+                plunger: true,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Cabinet button boards.  Controllers send one text line per change, over UDP (one or more
+//! lines per datagram) or a serial port:
+//!
+//! ```text
+//! flipper_left 1
+//! flipper_left 0
+//! plunger_pos 200
+//! ```
+//!
+//! Buttons are `flipper_left`, `flipper_right`, `nudge`, `plunger` and `start`, with 1 for
+//! pressed and 0 for released.  `plunger_pos` is an analog plunger, from 0 at rest to 255
+//! pulled all the way back.  Button changes are debounced: once a button changes, it keeps
+//! its new state for the debounce time, and only then follows the controller again.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, ErrorKind},
+    net::{ToSocketAddrs, UdpSocket},
+    path::Path,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::{Duration, Instant},
+};
+
+use enum_map::{Enum, EnumMap};
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::view::View;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Enum)]
+pub enum Button {
+    FlipperLeft,
+    FlipperRight,
+    Nudge,
+    Plunger,
+    Start,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum InputEvent {
+    Button { button: Button, pressed: bool },
+    PlungerPos(u8),
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct ButtonState {
+    pressed: bool,
+    /// The last state the controller reported.
+    raw: bool,
+    locked_until: Option<Instant>,
+}
+
+pub struct InputServer {
+    udp: Option<UdpSocket>,
+    serial: Option<Receiver<String>>,
+    debounce: Duration,
+    buttons: EnumMap<Button, ButtonState>,
+}
+
+fn parse_line(line: &str) -> Result<InputEvent, String> {
+    let Some((name, value)) = line.split_once(' ') else {
+        return Err(format!("expected name and value: {line}"));
+    };
+    let value: u8 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("bad value: {line}"))?;
+    let button = match name {
+        "flipper_left" => Button::FlipperLeft,
+        "flipper_right" => Button::FlipperRight,
+        "nudge" => Button::Nudge,
+        "plunger" => Button::Plunger,
+        "start" => Button::Start,
+        "plunger_pos" => return Ok(InputEvent::PlungerPos(value)),
+        _ => return Err(format!("unknown input: {line}")),
+    };
+    match value {
+        0 | 1 => Ok(InputEvent::Button {
+            button,
+            pressed: value == 1,
+        }),
+        _ => Err(format!("buttons are 0 or 1: {line}")),
+    }
+}
+
+impl InputServer {
+    pub fn new(debounce: Duration) -> Self {
+        Self {
+            udp: None,
+            serial: None,
+            debounce,
+            buttons: EnumMap::default(),
+        }
+    }
+
+    pub fn bind_udp(&mut self, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        self.udp = Some(socket);
+        Ok(())
+    }
+
+    /// Reads lines from a serial port or pty, on a thread of its own since ports can't be
+    /// polled portably.  Set the port's speed beforehand, with `stty` or `mode`.
+    pub fn open_serial(&mut self, path: &Path) -> std::io::Result<()> {
+        let file = File::open(path)?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(file).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        self.serial = Some(rx);
+        Ok(())
+    }
+
+    /// Calls `handler` with every input received since the last call, and with the buttons
+    /// whose debounce time ran out in a different state.  Broken lines are passed as errors.
+    pub fn poll(&mut self, handler: impl FnMut(Result<InputEvent, String>)) {
+        self.poll_at(Instant::now(), handler);
+    }
+
+    /// Like [`InputServer::poll`], but passes the input to `view`, buttons as the keys they
+    /// stand for.
+    pub fn poll_view(
+        &mut self,
+        mut view: Option<&mut (dyn View + '_)>,
+        mut error: impl FnMut(String),
+    ) {
+        self.poll(|event| {
+            let Some(view) = &mut view else {
+                return;
+            };
+            match event {
+                Ok(InputEvent::Button { button, pressed }) => {
+                    let key = match button {
+                        Button::FlipperLeft => KeyCode::ShiftLeft,
+                        Button::FlipperRight => KeyCode::ShiftRight,
+                        Button::Nudge => KeyCode::Space,
+                        Button::Plunger => KeyCode::ArrowDown,
+                        Button::Start => KeyCode::Enter,
+                    };
+                    view.handle_key(
+                        key,
+                        if pressed {
+                            ElementState::Pressed
+                        } else {
+                            ElementState::Released
+                        },
+                    );
+                }
+                Ok(InputEvent::PlungerPos(pos)) => view.handle_plunger(pos),
+                Err(e) => error(e),
+            }
+        });
+    }
+
+    fn poll_at(&mut self, now: Instant, mut handler: impl FnMut(Result<InputEvent, String>)) {
+        let mut lines = vec![];
+        if let Some(udp) = &self.udp {
+            let mut buf = [0; 0x1000];
+            loop {
+                match udp.recv(&mut buf) {
+                    Ok(n) => lines.extend(
+                        String::from_utf8_lossy(&buf[..n])
+                            .lines()
+                            .map(str::to_string),
+                    ),
+                    // Some systems report errors from earlier sends here.
+                    Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+                    Err(_) => break,
+                }
+            }
+        }
+        if let Some(serial) = &self.serial {
+            loop {
+                match serial.try_recv() {
+                    Ok(line) => lines.push(line),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        handler(Err("serial port closed".to_string()));
+                        self.serial = None;
+                        break;
+                    }
+                }
+            }
+        }
+        for (button, state) in &mut self.buttons {
+            if state.locked_until.is_some_and(|until| now >= until) {
+                state.locked_until = None;
+                if state.raw != state.pressed {
+                    state.pressed = state.raw;
+                    state.locked_until = Some(now + self.debounce);
+                    handler(Ok(InputEvent::Button {
+                        button,
+                        pressed: state.pressed,
+                    }));
+                }
+            }
+        }
+        for line in lines {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match parse_line(line) {
+                Ok(InputEvent::Button { button, pressed }) => {
+                    let state = &mut self.buttons[button];
+                    state.raw = pressed;
+                    if state.locked_until.is_none() && state.pressed != pressed {
+                        state.pressed = pressed;
+                        state.locked_until = Some(now + self.debounce);
+                        handler(Ok(InputEvent::Button { button, pressed }));
+                    }
+                }
+                res => handler(res),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debounce() {
+        let mut server = InputServer::new(Duration::from_millis(10));
+        server.bind_udp("127.0.0.1:0").unwrap();
+        let addr = server.udp.as_ref().unwrap().local_addr().unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let start = Instant::now();
+        let poll = |server: &mut InputServer, ms: u64| {
+            let mut events = vec![];
+            server.poll_at(start + Duration::from_millis(ms), |event| {
+                events.push(event)
+            });
+            events
+        };
+        let press = |pressed| {
+            Ok(InputEvent::Button {
+                button: Button::FlipperLeft,
+                pressed,
+            })
+        };
+        // A bouncing press comes through once, right away.
+        client
+            .send_to(b"flipper_left 1\nflipper_left 0\nflipper_left 1\n", addr)
+            .unwrap();
+        let mut events = vec![];
+        while events.is_empty() {
+            events = poll(&mut server, 0);
+        }
+        assert_eq!(events, [press(true)]);
+        // A release within the debounce time waits for it to run out.
+        client
+            .send_to(b"flipper_left 0\nplunger_pos 128\n", addr)
+            .unwrap();
+        let mut events = vec![];
+        while events.is_empty() {
+            events = poll(&mut server, 5);
+        }
+        assert_eq!(events, [Ok(InputEvent::PlungerPos(128))]);
+        assert_eq!(poll(&mut server, 10), [press(false)]);
+        assert_eq!(poll(&mut server, 30), []);
+        assert!(parse_line("tilt 1").is_err());
+        assert!(parse_line("nudge 2").is_err());
+    }
+}
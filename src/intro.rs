@@ -1,8 +1,10 @@
 #![allow(clippy::collapsible_else_if)]
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use arrayvec::ArrayVec;
+use enum_map::Enum;
 use unnamed_entity::EntityId;
 use winit::event::{ElementState, TouchPhase};
 use winit::keyboard::KeyCode;
@@ -11,19 +13,25 @@ use crate::icons::IconKind;
 use crate::{
     assets::{
         iff::Image,
-        intro::{Assets, SlideId, TableSet, TextPageId, CGA_FONT},
+        intro::{Assets, SlideId, TableSet, TextPage, TextPageId, CGA_FONT},
+        table::physics::RollTrigger,
     },
-    config::{Config, Resolution, ScrollSpeed, TableId},
+    config::{stat_rolls, Config, Resolution, ScrollSpeed, TableId},
     sound::player::Player,
     view::{Action, Route, View},
 };
 
+// Lines on a statistics page, below the table name.
+const STATS_PER_PAGE: usize = 10;
+
 pub struct Intro {
     player: Player,
     assets: Assets,
     config: Config,
     state: State,
     text_page: TextPageId,
+    /// Which four entries of the high score lists are shown.
+    hiscore_page: usize,
     key: KeyPress,
     left_state: LeftState,
     left_is_options: bool,
@@ -51,7 +59,7 @@ enum KeyPress {
     Option(u8),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 enum State {
     Slide(SlideId, SlideState),
     InitDelay(u8),
@@ -92,8 +100,42 @@ enum LeftState {
 
 impl Intro {
     pub fn new(prg: &[u8], module: &[u8], config: Config, table: Option<TableId>) -> Intro {
-        let module = crate::sound::loader::load(module);
-        let player = crate::sound::player::play(module, None);
+        Intro::from_assets(Assets::load(prg), module, config, table)
+    }
+
+    pub fn from_assets(
+        assets: Assets,
+        module: &[u8],
+        config: Config,
+        table: Option<TableId>,
+    ) -> Intro {
+        Self::with_player(assets, module, config, table, true)
+    }
+
+    /// An intro without sound, which doesn't need an audio device.  The slides are timed by
+    /// the music, which is played along with the frames instead.
+    pub fn from_assets_silent(
+        assets: Assets,
+        module: &[u8],
+        config: Config,
+        table: Option<TableId>,
+    ) -> Intro {
+        Self::with_player(assets, module, config, table, false)
+    }
+
+    fn with_player(
+        assets: Assets,
+        module: &[u8],
+        config: Config,
+        table: Option<TableId>,
+        sound: bool,
+    ) -> Intro {
+        let module = Arc::new(crate::sound::loader::load(module));
+        let player = if sound {
+            crate::sound::player::play(module, None)
+        } else {
+            crate::sound::player::play_silent(module, None)
+        };
         let (state, text_page) = match table {
             Some(TableId::Table1 | TableId::Table2) => {
                 (State::InitDelay(0), TextPageId::from_idx(0))
@@ -108,10 +150,11 @@ impl Intro {
         };
         Intro {
             player,
-            assets: Assets::load(prg),
+            assets,
             config,
             state,
             text_page,
+            hiscore_page: 0,
             key: KeyPress::None,
             left_state: LeftState::None,
             left_is_options: false,
@@ -251,18 +294,40 @@ impl Intro {
         }
     }
 
+    /// The text pages from the game files are followed by one statistics page per table.
+    fn stats_page(&self) -> Option<TableId> {
+        let idx = self
+            .text_page
+            .to_idx()
+            .checked_sub(self.assets.text_pages.len())?;
+        Some(TableId::from_usize(idx))
+    }
+
+    /// The two tables shown next to the current text page when the screen isn't vertical.
+    /// Text pages alternate between the first and the second pair, and a statistics page
+    /// goes with the pair its table is in.
+    fn shown_tables(&self) -> [TableId; 2] {
+        let table34 = match self.stats_page() {
+            Some(table) => matches!(table, TableId::Table3 | TableId::Table4),
+            None => !self.text_page.to_idx().is_multiple_of(2),
+        };
+        if table34 {
+            [TableId::Table3, TableId::Table4]
+        } else {
+            [TableId::Table1, TableId::Table2]
+        }
+    }
+
     fn render_tables(&self, data: &mut [u8], pal: &mut [(u8, u8, u8)], f: impl Fn(usize) -> bool) {
         if self.is_vertical() {
             self.render_table(data, pal, &f, TableId::Table1, 12, false);
             self.render_table(data, pal, &f, TableId::Table2, 132, false);
             self.render_table(data, pal, &f, TableId::Table3, 252, true);
             self.render_table(data, pal, &f, TableId::Table4, 372, true);
-        } else if self.text_page.to_idx() % 2 == 0 {
-            self.render_table(data, pal, &f, TableId::Table1, 10, false);
-            self.render_table(data, pal, &f, TableId::Table2, 135, true);
         } else {
-            self.render_table(data, pal, &f, TableId::Table3, 10, false);
-            self.render_table(data, pal, &f, TableId::Table4, 135, true);
+            let [top, bottom] = self.shown_tables();
+            self.render_table(data, pal, &f, top, 10, false);
+            self.render_table(data, pal, &f, bottom, 135, true);
         }
     }
 
@@ -295,22 +360,113 @@ impl Intro {
         }
     }
 
-    fn render_hiscores(&self, data: &mut [u8], font: &Image, table: TableId, y: usize) {
-        let name = match table {
+    fn table_name(table: TableId) -> &'static [u8; 24] {
+        match table {
             TableId::Table1 => b"     PARTY LAND         ",
             TableId::Table2 => b"     SPEED DEVILS       ",
             TableId::Table3 => b"     BILLION DOLLAR     ",
             TableId::Table4 => b"     STONES N BONES     ",
-        };
-        self.render_line(data, font, name, y);
-        for (i, score) in self.config.high_scores[table].iter().enumerate() {
+        }
+    }
+
+    fn render_hiscores(&self, data: &mut [u8], font: &Image, table: TableId, y: usize) {
+        self.render_line(data, font, Self::table_name(table), y);
+        let first = self.hiscore_page * 4;
+        for (i, score) in self.config.high_scores[table]
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(4)
+        {
             let mut line = [b' '; 24];
-            line[2] = b'1' + (i as u8);
+            let place = i + 1;
+            if place >= 10 {
+                line[1] = b'0' + (place / 10) as u8;
+            }
+            line[2] = b'0' + (place % 10) as u8;
             line[3] = b'.';
             line[5..8].copy_from_slice(&score.name);
             line[9] = b'-';
             line[11..23].copy_from_slice(&score.score.to_ascii());
-            self.render_line(data, font, &line, y + (i + 1) * 18);
+            self.render_line(data, font, &line, y + (i - first + 1) * 18);
+        }
+    }
+
+    /// How a counted ramp or orbit is called on the statistics pages.
+    fn roll_label(kind: RollTrigger) -> &'static [u8] {
+        match kind {
+            RollTrigger::PartyRampCyclone => b"CYCLONE RAMP",
+            RollTrigger::PartyRampSnack => b"SNACK RAMP",
+            RollTrigger::PartySkyrideRamp => b"SKYRIDE RAMP",
+            RollTrigger::PartyOrbitTopLeft => b"TOP LEFT ORBIT",
+            RollTrigger::PartyOrbitTopRight => b"TOP RIGHT ORBIT",
+            RollTrigger::SpeedRampOffroad => b"OFFROAD RAMP",
+            RollTrigger::SpeedRampMilesLeft => b"LEFT MILES RAMP",
+            RollTrigger::SpeedRampMilesRight => b"RIGHT MILES RAMP",
+            RollTrigger::SpeedPitLoopJump => b"PIT LOOP JUMP",
+            RollTrigger::ShowRampTop | RollTrigger::StonesRampTop => b"TOP RAMP",
+            RollTrigger::ShowRampSkill => b"SKILL RAMP",
+            RollTrigger::ShowRampRight => b"RIGHT RAMP",
+            RollTrigger::ShowRampLoop => b"LOOP",
+            RollTrigger::ShowOrbitLeft => b"LEFT ORBIT",
+            RollTrigger::ShowOrbitRight => b"RIGHT ORBIT",
+            RollTrigger::StonesRampTower => b"TOWER RAMP",
+            RollTrigger::StonesRampScreams => b"SCREAMS RAMP",
+            RollTrigger::StonesRampLeftToLane => b"LEFT RAMP TO LANE",
+            RollTrigger::StonesRampLeftToVault => b"LEFT RAMP TO VAULT",
+            _ => b"RAMP",
+        }
+    }
+
+    /// Every line of a table's statistics, with the ramps and orbits one by one at the end.
+    fn stats_lines(&self, table: TableId) -> Vec<(&'static [u8], Vec<u8>)> {
+        let stats = &self.config.stats[table];
+        let average = match stats.total_score.checked_div(stats.games.into()) {
+            Some(average) => average.to_string().into_bytes(),
+            None => b"0".to_vec(),
+        };
+        let mut lines: Vec<(&[u8], Vec<u8>)> = vec![
+            (b"GAMES PLAYED", stats.games.to_string().into_bytes()),
+            (b"AVERAGE", average),
+            (b"BEST", stats.best_score.to_ascii().to_vec()),
+            (
+                b"BALLS DRAINED",
+                stats.balls_drained.to_string().into_bytes(),
+            ),
+            (b"EXTRA BALLS", stats.extra_balls.to_string().into_bytes()),
+            (b"TILTS", stats.tilts.to_string().into_bytes()),
+            (b"JACKPOTS", stats.jackpots.to_string().into_bytes()),
+            (
+                b"MODES STARTED",
+                stats.modes_started.to_string().into_bytes(),
+            ),
+            (
+                b"MODES COMPLETED",
+                stats.modes_completed.to_string().into_bytes(),
+            ),
+            (b"MATCH WINS", stats.match_wins.to_string().into_bytes()),
+        ];
+        for (&kind, count) in stat_rolls(table).iter().zip(stats.rolls) {
+            lines.push((Self::roll_label(kind), count.to_string().into_bytes()));
+        }
+        lines
+    }
+
+    fn render_stats(&self, data: &mut [u8], font: &Image, table: TableId) {
+        let base = if self.is_vertical() { 120 } else { 0 };
+        self.render_line(data, font, Self::table_name(table), 14 + base);
+        for (ty, (label, value)) in self
+            .stats_lines(table)
+            .into_iter()
+            .skip(self.hiscore_page * STATS_PER_PAGE)
+            .take(STATS_PER_PAGE)
+            .enumerate()
+        {
+            let mut line = [b' '; 24];
+            line[..label.len()].copy_from_slice(label);
+            let value = &value[value.len().saturating_sub(24 - label.len() - 1)..];
+            line[24 - value.len()..].copy_from_slice(value);
+            self.render_line(data, font, &line, 14 + (ty + 1) * 18 + base);
         }
     }
 
@@ -321,9 +477,13 @@ impl Intro {
             (&self.assets.font_hq, &self.assets.hiscores_hq)
         };
         pal[0x10..0x20].copy_from_slice(&font.cmap);
+        if let Some(table) = self.stats_page() {
+            self.render_stats(data, font, table);
+            return;
+        }
         let page = &self.assets.text_pages[self.text_page];
         match page {
-            crate::assets::intro::TextPage::HiScores(tset) => {
+            TextPage::HiScores(tset) => {
                 for y in 0..hiscores.data.dim().1 {
                     for x in 0..hiscores.data.dim().0 {
                         let pidx = y * 640 * 2 + x + 184;
@@ -350,7 +510,7 @@ impl Intro {
                     }
                 }
             }
-            crate::assets::intro::TextPage::Text(text) => {
+            TextPage::Text(text) => {
                 let base = if self.is_vertical() { 120 } else { 0 };
                 for (ty, line) in text.iter().enumerate() {
                     self.render_line(data, font, line, 14 + ty * 18 + base);
@@ -382,6 +542,7 @@ impl Intro {
             b"  INGAME MUSIC:         ".to_vec(),
             b"  RESOLUTION:           ".to_vec(),
             b"  COLOR MODE:           ".to_vec(),
+            b"  GAME SPEED:           ".to_vec(),
             vec![],
             b"  SAVE AND EXIT         ".to_vec(),
         ];
@@ -418,17 +579,45 @@ impl Intro {
             lines[7][16..21].copy_from_slice(b"COLOR");
         }
 
+        let speed = self.config.options.game_speed.to_string();
+        lines[8][16..16 + speed.len()].copy_from_slice(speed.as_bytes());
+
         for (ty, line) in lines.into_iter().enumerate() {
             self.render_line(data, font, &line, 14 + ty * pitch);
         }
 
         if let Some(cursor) = cursor {
-            let pos = if cursor == 6 { 9 } else { cursor as usize + 2 };
+            let pos = if cursor == 7 { 10 } else { cursor as usize + 2 };
             self.render_char(data, font, b'>', 175, 14 + pos * pitch);
         }
     }
 
+    /// Returns how many pages of four entries the longest high score list on the current text
+    /// page has, how many pages a statistics page takes, or 0 for other pages.
+    fn hiscore_pages(&self) -> usize {
+        if let Some(table) = self.stats_page() {
+            return self.stats_lines(table).len().div_ceil(STATS_PER_PAGE);
+        }
+        let tables: &[TableId] = match self.assets.text_pages.get(self.text_page) {
+            Some(TextPage::HiScores(_)) if self.is_vertical() => &[
+                TableId::Table1,
+                TableId::Table2,
+                TableId::Table3,
+                TableId::Table4,
+            ],
+            Some(TextPage::HiScores(TableSet::Table12)) => &[TableId::Table1, TableId::Table2],
+            Some(TextPage::HiScores(TableSet::Table34)) => &[TableId::Table3, TableId::Table4],
+            _ => &[],
+        };
+        tables
+            .iter()
+            .map(|&table| self.config.high_scores[table].len().div_ceil(4))
+            .max()
+            .unwrap_or(0)
+    }
+
     fn next_page(&mut self) {
+        self.hiscore_page = 0;
         if self.is_vertical() {
             self.text_page = TextPageId::from_idx(match self.text_page.to_idx() {
                 0 | 1 => 2,
@@ -436,12 +625,14 @@ impl Intro {
                 3 => 4,
                 4 | 5 => 6,
                 6 => 7,
+                7 => self.assets.text_pages.len(),
+                idx if idx >= self.assets.text_pages.len() => idx + 1,
                 _ => 0,
             });
         } else {
             self.text_page += 1;
         }
-        if self.text_page == self.assets.text_pages.next_id() {
+        if self.text_page.to_idx() == self.assets.text_pages.len() + TableId::LENGTH {
             self.text_page = TextPageId::from_idx(0);
         }
     }
@@ -471,18 +662,11 @@ impl Intro {
                             _ => TableId::Table4,
                         }
                     } else {
-                        if self.text_page.to_idx() % 2 == 0 {
-                            if pos.1 < 240 {
-                                TableId::Table1
-                            } else {
-                                TableId::Table2
-                            }
+                        let [top, bottom] = self.shown_tables();
+                        if pos.1 < 240 {
+                            top
                         } else {
-                            if pos.1 < 240 {
-                                TableId::Table3
-                            } else {
-                                TableId::Table4
-                            }
+                            bottom
                         }
                     };
                     self.key = KeyPress::Table(table);
@@ -513,8 +697,8 @@ impl Intro {
                         }
                     };
                     match idx {
-                        2..=7 => self.key = KeyPress::Option(idx as u8 - 2),
-                        9 => self.key = KeyPress::Escape,
+                        2..=8 => self.key = KeyPress::Option(idx as u8 - 2),
+                        10 => self.key = KeyPress::Escape,
                         _ => (),
                     }
                 }
@@ -550,6 +734,13 @@ impl Intro {
                 };
             }
             5 => self.config.options.mono = !self.config.options.mono,
+            6 => {
+                self.config.options.game_speed = if self.config.options.game_speed <= 50 {
+                    100
+                } else {
+                    self.config.options.game_speed - 10
+                };
+            }
             _ => self.state = State::OptionsFadeOut(0),
         }
     }
@@ -589,6 +780,7 @@ impl View for Intro {
     }
 
     fn run_frame(&mut self) -> Action {
+        self.player.advance_frame(self.get_fps());
         match self.left_state {
             LeftState::None => {}
             LeftState::Image(ref mut n) => {
@@ -622,6 +814,7 @@ impl View for Intro {
                 }
             }
         }
+        let hiscore_pages = self.hiscore_pages();
         match self.state {
             State::Slide(ref mut slide_idx, ref mut sstate) => {
                 let slide = &self.assets.slides[*slide_idx];
@@ -693,10 +886,10 @@ impl View for Intro {
                     self.state = State::Tables(0);
                 }
             }
-            State::TablesFadeOut(ref mut n, action) => {
+            State::TablesFadeOut(ref mut n, ref action) => {
                 self.player.set_master_volume(0x100 * (80 - *n) as u32 / 80);
                 if *n >= 80 {
-                    return action;
+                    return action.clone();
                 }
                 *n += 1;
             }
@@ -769,6 +962,15 @@ impl View for Intro {
                     KeyPress::Enter | KeyPress::Space | KeyPress::Escape => {
                         self.state = State::TextFadeOut(0, IntroAction::SkipToTables);
                     }
+                    // Paging through the high score lists or statistics keeps the page up for longer.
+                    KeyPress::Down if self.hiscore_page + 1 < hiscore_pages => {
+                        self.hiscore_page += 1;
+                        *n = 0;
+                    }
+                    KeyPress::Up if self.hiscore_page != 0 => {
+                        self.hiscore_page -= 1;
+                        *n = 0;
+                    }
                     _ => {
                         if *n >= 420 {
                             self.state = State::TextFadeOut(0, IntroAction::SkipToTables);
@@ -820,13 +1022,13 @@ impl View for Intro {
                     }
                     KeyPress::Up => {
                         if *cursor == 0 {
-                            *cursor = 6;
+                            *cursor = 7;
                         } else {
                             *cursor -= 1;
                         }
                     }
                     KeyPress::Down => {
-                        if *cursor == 6 {
+                        if *cursor == 7 {
                             *cursor = 0;
                         } else {
                             *cursor += 1;
@@ -845,10 +1047,10 @@ impl View for Intro {
                     return Action::SaveOptions(self.config.options);
                 }
             }
-            State::FadeOut(ref mut n, action) => {
+            State::FadeOut(ref mut n, ref action) => {
                 self.player.set_master_volume(0x100 * (80 - *n) as u32 / 80);
                 if *n >= 80 {
-                    return action;
+                    return action.clone();
                 }
                 *n += 1;
             }
@@ -875,6 +1077,10 @@ impl View for Intro {
         }
     }
 
+    fn set_turbo(&mut self, factor: u8) {
+        self.player.set_tempo(factor as u32 * 100);
+    }
+
     fn render(&self, data: &mut [u8], pal: &mut [(u8, u8, u8)]) {
         match self.state {
             State::Slide(sidx, sstate) => {
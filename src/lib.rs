@@ -1,9 +1,16 @@
 pub mod assets;
 pub mod bcd;
+pub mod cache;
 pub mod config;
+#[cfg(test)]
+mod golden;
+pub mod gym;
 pub mod icons;
+pub mod input;
 pub mod intro;
+pub mod rpc;
 pub mod sound;
+pub mod stream;
 pub mod table;
 pub mod view;
 
@@ -1,12 +1,25 @@
 use game_loop::game_loop;
-use std::path::PathBuf;
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use enum_map::EnumMap;
 use pfr::{
-    config::{save_high_scores, Config, FileConfigStore, Resolution, TableId},
+    assets::{
+        bundle::{Bundle, BUNDLE_FILE},
+        table::Assets,
+    },
+    cache::{AssetCache, TableData},
+    config::{
+        save_high_scores, save_practice_shots, save_stats, Config, FileConfigStore, Resolution,
+        TableId,
+    },
     icons::IconKind,
     intro::Intro,
-    table::Table,
+    sound::loader,
+    table::{Assist, AutoplayMode, Table, Trace},
     view::{Action, Route, View},
 };
 use pixels::{Pixels, SurfaceTexture};
@@ -18,13 +31,43 @@ use winit::{
     keyboard::{KeyCode, PhysicalKey},
     window::{Fullscreen, Window},
 };
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    pfr::{
+        input::InputServer,
+        rpc::RpcControl,
+        stream::StreamOutput,
+        table::{DmLayout, DmPngDump, DmSink, LampMap, LampOutput, LampProtocol},
+    },
+    std::{cell::RefCell, io::Write, rc::Rc},
+};
 
 struct Game {
     pixels: Pixels,
     view: Option<Box<dyn View>>,
     config: Config,
+    bundle: Option<Arc<Bundle>>,
+    cache: AssetCache,
     args: Args,
     dims: (u32, u32),
+    /// Mouse position in screen pixels.
+    cursor: (i32, i32),
+    /// Frames run for every frame shown.
+    turbo: u8,
+    /// Ball recordings of the tables left earlier in the session.
+    traces: EnumMap<TableId, Option<Trace>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    events: Option<Rc<RefCell<StreamOutput>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    feedback: Option<Rc<RefCell<StreamOutput>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    dmd: Option<Rc<RefCell<StreamOutput>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    dmd_png: Option<Rc<RefCell<DmPngDump>>>,
+    #[cfg(not(target_arch = "wasm32"))]
+    rpc: Option<RpcControl>,
+    #[cfg(not(target_arch = "wasm32"))]
+    input: Option<InputServer>,
 }
 
 #[derive(Parser)]
@@ -33,6 +76,104 @@ struct Args {
     table: Option<u8>,
     #[clap(long)]
     touch: bool,
+    /// Extract all tables in the background while the intro is playing.
+    #[clap(long)]
+    preload: bool,
+    /// Practice mode: drag with the mouse to throw the ball, high scores are not recorded.
+    #[clap(long)]
+    practice: bool,
+    /// Enable the frame-stepping debugger: F9 freezes the table.
+    #[clap(long)]
+    debug: bool,
+    /// Play a demo game after half a minute in attract mode.  Any key ends it.
+    #[clap(long)]
+    demo: bool,
+    /// Raise the flippers automatically when the ball comes at them.
+    #[clap(long)]
+    flipper_assist: bool,
+    /// Play with a single button: any flipper key or the plunger key starts the game, works
+    /// the plunger, and raises both flippers.
+    #[clap(long)]
+    one_switch: bool,
+    /// Record where the ball goes, and write a heatmap of each layer to this directory when
+    /// leaving the table.  Every visit to a table in the session adds to the same heatmap.
+    #[clap(long)]
+    heatmap: Option<PathBuf>,
+    /// Also write every recorded ball position to the heatmap directory.
+    #[clap(long, requires = "heatmap")]
+    trajectory: Option<TrajectoryFormat>,
+    /// Append a breakdown of where the points came from to this file after every game.
+    #[clap(long)]
+    score_report: Option<PathBuf>,
+    /// Write game events as JSON lines to this file.
+    #[clap(long)]
+    events: Option<PathBuf>,
+    /// Send game events as JSON lines to every client connected to this TCP address.
+    #[clap(long)]
+    events_listen: Option<String>,
+    /// Accept JSON-RPC requests to inspect and control the game on this TCP address.
+    #[clap(long)]
+    rpc: Option<String>,
+    /// Let the `screenshot` RPC method write to this directory.
+    #[clap(long, requires = "rpc")]
+    rpc_screenshots: Option<PathBuf>,
+    /// Send the table's lights to physical lamps at this UDP address.
+    #[clap(long)]
+    lamps: Option<SocketAddr>,
+    /// How the lamp states are sent.
+    #[clap(long, value_enum, default_value_t = LampFormat::Artnet, requires = "lamps")]
+    lamps_protocol: LampFormat,
+    /// Write a line to this file, serial port or pty whenever a coil fires: a flipper, a
+    /// bumper, a kicker, the trough, the plunger or the tilt.
+    #[clap(long)]
+    feedback: Option<PathBuf>,
+    /// Send the coil lines to every client connected to this TCP address.
+    #[clap(long)]
+    feedback_listen: Option<String>,
+    /// Stream the dot matrix in the raw protocol to this file, serial port or pty.
+    #[clap(long)]
+    dmd: Option<PathBuf>,
+    /// Stream the dot matrix to every client connected to this TCP address.
+    #[clap(long)]
+    dmd_listen: Option<String>,
+    /// Write every new dot matrix frame to this directory as a PNG.
+    #[clap(long)]
+    dmd_png: Option<PathBuf>,
+    /// Size of the external dot matrix display, such as 128x32.
+    #[clap(long, value_parser = parse_dmd_size, default_value = "160x16")]
+    dmd_size: (u16, u16),
+    /// Display dots per dot matrix dot.  By default, the largest scale that fits.
+    #[clap(long)]
+    dmd_scale: Option<f32>,
+    /// Put the dot matrix in the top left corner of the display instead of centering it.
+    #[clap(long)]
+    dmd_top_left: bool,
+    /// Accept button board input on this UDP address.
+    #[clap(long)]
+    input_udp: Option<String>,
+    /// Accept button board input from this serial port or pty.
+    #[clap(long)]
+    input_serial: Option<PathBuf>,
+    /// How long a button keeps a new state before following the board again, in milliseconds.
+    #[clap(long, default_value_t = 5)]
+    input_debounce: u64,
+    /// Run the game this many times as fast, music included.  Tab switches between 1, 2 and 4
+    /// while playing.
+    #[clap(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=8))]
+    turbo: u8,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum LampFormat {
+    Artnet,
+    /// The universe number (big-endian) followed by its 512 channels.
+    Raw,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum TrajectoryFormat {
+    Csv,
+    Json,
 }
 
 fn main() {
@@ -51,6 +192,33 @@ fn main() {
             data: PathBuf::from(r"meow"),
             table: Option::from(table),
             touch: true, // TODO
+            preload: false,
+            practice: false,
+            debug: false,
+            demo: false,
+            flipper_assist: false,
+            one_switch: false,
+            heatmap: None,
+            trajectory: None,
+            score_report: None,
+            events: None,
+            events_listen: None,
+            rpc: None,
+            rpc_screenshots: None,
+            lamps: None,
+            lamps_protocol: LampFormat::Artnet,
+            feedback: None,
+            feedback_listen: None,
+            dmd: None,
+            dmd_listen: None,
+            dmd_png: None,
+            dmd_size: (160, 16),
+            dmd_scale: None,
+            dmd_top_left: false,
+            input_udp: None,
+            input_serial: None,
+            input_debounce: 5,
+            turbo: 1,
         };
         //std::panic::set_hook(Box::new(console_error_panic_hook::hook));
         //console_log::init_with_level(log::Level::Trace).expect("error initializing logger");
@@ -68,9 +236,193 @@ fn main() {
     }
 }
 
+fn load_table(path: &Path, bundle: Option<&Bundle>, table: TableId) -> TableData {
+    let (prg, module, scripts, art) = match table {
+        TableId::Table1 => ("TABLE1.PRG", "TABLE1.MOD", "TABLE1.SCR", "TABLE1.ART"),
+        TableId::Table2 => ("TABLE2.PRG", "TABLE2.MOD", "TABLE2.SCR", "TABLE2.ART"),
+        TableId::Table3 => ("TABLE3.PRG", "TABLE3.MOD", "TABLE3.SCR", "TABLE3.ART"),
+        TableId::Table4 => ("TABLE4.PRG", "TABLE4.MOD", "TABLE4.SCR", "TABLE4.ART"),
+    };
+    #[allow(unused_mut)]
+    let mut assets = match bundle {
+        Some(bundle) => bundle.tables[table].clone(),
+        None => Assets::load(&read_data(path, prg).unwrap(), table),
+    };
+    // Scripts can use the animations an art pack adds, and its messages apply to the scripts
+    // in use, by their original numbers.
+    #[cfg(not(target_arch = "wasm32"))]
+    load_art_anims(&mut assets, &path.join(art));
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Ok(src) = std::fs::read_to_string(path.join(scripts)) {
+        if let Err(e) = assets.load_scripts(&src) {
+            eprintln!("{scripts}: {e}, using original scripts");
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    load_art_msgs(&mut assets, &path.join(art));
+    #[cfg(target_arch = "wasm32")]
+    let _ = (scripts, art);
+    let module = loader::load(&read_data(path, module).unwrap());
+    TableData {
+        assets: Arc::new(assets),
+        module: Arc::new(module),
+    }
+}
+
+/// Applies the `animN.gif` / `animN.png` files of a dot matrix art pack, replacing or adding
+/// animations.  Broken files are skipped.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_art_anims(assets: &mut Assets, dir: &Path) {
+    use pfr::assets::table::art::decode_dm_anim;
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut anims = vec![];
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+        let Some((stem, ext)) = name.rsplit_once('.') else {
+            continue;
+        };
+        if !matches!(ext, "gif" | "png") {
+            continue;
+        }
+        if let Some(idx) = stem.strip_prefix("anim").and_then(|idx| idx.parse().ok()) {
+            anims.push((idx, entry.path()));
+        }
+    }
+    anims.sort();
+    for (idx, file) in anims {
+        let res = std::fs::read(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|data| decode_dm_anim(&data, &assets.dm_palette).map_err(|e| e.to_string()))
+            .and_then(|anim| assets.set_dm_anim(idx, &anim).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            eprintln!("{}: {e}", file.display());
+        }
+    }
+}
+
+/// Applies the message overrides in an art pack's `msgs.txt`.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_art_msgs(assets: &mut Assets, dir: &Path) {
+    let file = dir.join("msgs.txt");
+    if let Ok(src) = std::fs::read_to_string(&file) {
+        if let Err(e) = assets.load_msgs(&src) {
+            eprintln!("{}: {e}, using original messages", file.display());
+        }
+    }
+}
+
+/// A stream for `--<name>` and `--<name>-listen`, if either is given.
+#[cfg(not(target_arch = "wasm32"))]
+fn open_stream(
+    name: &'static str,
+    file: Option<&Path>,
+    listen: Option<&str>,
+) -> Option<Rc<RefCell<StreamOutput>>> {
+    if file.is_none() && listen.is_none() {
+        return None;
+    }
+    let mut stream = StreamOutput::new(name);
+    if let Some(path) = file {
+        if let Err(e) = stream.create_file(path) {
+            eprintln!("{}: {e}", path.display());
+        }
+    }
+    if let Some(addr) = listen {
+        if let Err(e) = stream.listen(addr) {
+            eprintln!("{addr}: {e}");
+        }
+    }
+    Some(Rc::new(RefCell::new(stream)))
+}
+
+/// A `WIDTHxHEIGHT` display size.
+fn parse_dmd_size(s: &str) -> Result<(u16, u16), String> {
+    let (w, h) = s.split_once('x').ok_or("expected WIDTHxHEIGHT")?;
+    let w: u16 = w.parse().map_err(|e| format!("{w}: {e}"))?;
+    let h: u16 = h.parse().map_err(|e| format!("{h}: {e}"))?;
+    if w == 0 || h == 0 {
+        return Err("empty display".into());
+    }
+    Ok((w, h))
+}
+
+/// Reads the table's `TABLEn.LMP` lamp mapping, or maps every light in order if there is
+/// none.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_lamp_map(path: &Path, assets: &Assets) -> LampMap {
+    let name = match assets.table {
+        TableId::Table1 => "TABLE1.LMP",
+        TableId::Table2 => "TABLE2.LMP",
+        TableId::Table3 => "TABLE3.LMP",
+        TableId::Table4 => "TABLE4.LMP",
+    };
+    let Ok(src) = std::fs::read_to_string(path.join(name)) else {
+        return LampMap::new(assets);
+    };
+    LampMap::parse(&src, assets).unwrap_or_else(|e| {
+        eprintln!("{name}: {e}, mapping every light in order");
+        LampMap::new(assets)
+    })
+}
+
+/// Appends the score breakdown of a game that just ended, if there is one.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_score_report(game: &mut Game) {
+    let (Some(view), Some(path)) = (&mut game.view, &game.args.score_report) else {
+        return;
+    };
+    let Some(report) = view.take_breakdown_report() else {
+        return;
+    };
+    let res = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{report}"));
+    if let Err(e) = res {
+        eprintln!("{}: {e}", path.display());
+    }
+}
+
+/// Takes the ball recording of the current view, if there is one, to be resumed on the next
+/// visit to the table, and writes out everything recorded on that table so far.
+#[cfg(not(target_arch = "wasm32"))]
+fn save_trace(game: &mut Game) {
+    let (Some(view), Some(dir)) = (&mut game.view, &game.args.heatmap) else {
+        return;
+    };
+    let Some(trace) = view.take_trace() else {
+        return;
+    };
+    let trajectory = game.args.trajectory.map(|format| match format {
+        TrajectoryFormat::Csv => pfr::table::TrajectoryFormat::Csv,
+        TrajectoryFormat::Json => pfr::table::TrajectoryFormat::Json,
+    });
+    if let Err(e) = trace.save(dir, trajectory) {
+        eprintln!("{e}");
+    }
+    let table = trace.table();
+    game.traces[table] = Some(trace);
+}
+
+fn read_data(path: &Path, fname: &str) -> Option<Vec<u8>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read(path.join(fname)).ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = path;
+        Some(pfr::wasm::get_asset(fname))
+    }
+}
+
 async fn run(args: Args) {
     #[cfg(target_arch = "wasm32")]
-    use pfr::wasm::{bind_mobile_events, get_asset, WasmConfigStore};
+    use pfr::wasm::{bind_mobile_events, WasmConfigStore};
 
     #[cfg(target_arch = "wasm32")]
     let cstore = WasmConfigStore::new(&args.data);
@@ -78,6 +430,62 @@ async fn run(args: Args) {
     #[cfg(not(target_arch = "wasm32"))]
     let cstore = FileConfigStore::new(&args.data);
     let config = Config::load(&cstore);
+    #[cfg(not(target_arch = "wasm32"))]
+    let events = open_stream(
+        "events",
+        args.events.as_deref(),
+        args.events_listen.as_deref(),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    let feedback = open_stream(
+        "feedback",
+        args.feedback.as_deref(),
+        args.feedback_listen.as_deref(),
+    );
+    #[cfg(not(target_arch = "wasm32"))]
+    let dmd = open_stream("dmd", args.dmd.as_deref(), args.dmd_listen.as_deref());
+    #[cfg(not(target_arch = "wasm32"))]
+    let dmd_png = args.dmd_png.clone().and_then(|dir| {
+        DmPngDump::new(dir.clone())
+            .map_err(|e| eprintln!("{}: {e}", dir.display()))
+            .map(|dump| Rc::new(RefCell::new(dump)))
+            .ok()
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    let rpc = args.rpc.as_ref().and_then(|addr| {
+        match RpcControl::bind(addr, args.rpc_screenshots.clone()) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                eprintln!("{addr}: {e}");
+                None
+            }
+        }
+    });
+    #[cfg(not(target_arch = "wasm32"))]
+    let input = (args.input_udp.is_some() || args.input_serial.is_some()).then(|| {
+        let mut input = InputServer::new(std::time::Duration::from_millis(args.input_debounce));
+        if let Some(addr) = &args.input_udp {
+            if let Err(e) = input.bind_udp(addr) {
+                eprintln!("{addr}: {e}");
+            }
+        }
+        if let Some(path) = &args.input_serial {
+            if let Err(e) = input.open_serial(path) {
+                eprintln!("{}: {e}", path.display());
+            }
+        }
+        input
+    });
+    let bundle = read_data(&args.data, BUNDLE_FILE)
+        .and_then(|data| Bundle::load(&data))
+        .map(Arc::new);
+    let cache = AssetCache::new();
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.preload {
+        let path = args.data.clone();
+        let bundle = bundle.clone();
+        cache.preload(move |table| load_table(&path, bundle.as_deref(), table));
+    }
     let event_loop = EventLoop::new().unwrap();
     let mut dims = if config.options.resolution == Resolution::Full {
         (640, (576 + 33) * 2)
@@ -94,7 +502,8 @@ async fn run(args: Args) {
         .with_inner_size(PhysicalSize::new(dims.0, dims.1))
         .with_resizable(true);
     let window = event_loop.create_window(window_attributes).unwrap();
-    window.set_cursor_visible(false);
+    // Practice mode places the ball with the mouse.
+    window.set_cursor_visible(args.practice);
     let window = Arc::new(window);
 
     #[cfg(target_arch = "wasm32")]
@@ -169,12 +578,30 @@ async fn run(args: Args) {
         }
     };
 
+    let turbo = args.turbo;
     let game = Game {
         pixels,
         args,
         config,
+        bundle,
+        cache,
         view: None,
         dims,
+        cursor: (0, 0),
+        turbo,
+        traces: EnumMap::default(),
+        #[cfg(not(target_arch = "wasm32"))]
+        events,
+        #[cfg(not(target_arch = "wasm32"))]
+        feedback,
+        #[cfg(not(target_arch = "wasm32"))]
+        dmd,
+        #[cfg(not(target_arch = "wasm32"))]
+        dmd_png,
+        #[cfg(not(target_arch = "wasm32"))]
+        rpc,
+        #[cfg(not(target_arch = "wasm32"))]
+        input,
     };
 
     game_loop(
@@ -185,9 +612,24 @@ async fn run(args: Args) {
         0.2,
         move |g| {
             // update
-            let mut action = match g.game.view {
-                Some(ref mut view) => view.run_frame(),
-                None => Action::Navigate(match g.game.args.table {
+            // A table switch requested over RPC replaces this frame.
+            #[cfg(not(target_arch = "wasm32"))]
+            let rpc_route = g.game.rpc.as_mut().and_then(RpcControl::take_route);
+            #[cfg(target_arch = "wasm32")]
+            let rpc_route = None;
+            let mut action = match (rpc_route, &mut g.game.view) {
+                (Some(route), _) => Action::Navigate(route),
+                (None, Some(view)) => {
+                    let mut action = Action::None;
+                    for _ in 0..g.game.turbo {
+                        action = view.run_frame();
+                        if action != Action::None {
+                            break;
+                        }
+                    }
+                    action
+                }
+                (None, None) => Action::Navigate(match g.game.args.table {
                     Some(t) => Route::Table(match t {
                         1 => TableId::Table1,
                         2 => TableId::Table2,
@@ -198,68 +640,145 @@ async fn run(args: Args) {
                     None => Route::Intro(None),
                 }),
             };
+            #[cfg(not(target_arch = "wasm32"))]
+            save_score_report(&mut g.game);
             if g.game.args.table.is_some() && matches!(action, Action::Navigate(Route::Intro(_))) {
                 action = Action::Exit;
             }
             match action {
                 Action::None => {}
                 Action::Navigate(route) => {
-                    let (prg, module) = match route {
-                        Route::Intro(None) => ("INTRO.PRG", "INTRO.MOD"),
-                        Route::Intro(Some(_)) => ("INTRO.PRG", "MOD2.MOD"),
-                        Route::Table(TableId::Table1) => ("TABLE1.PRG", "TABLE1.MOD"),
-                        Route::Table(TableId::Table2) => ("TABLE2.PRG", "TABLE2.MOD"),
-                        Route::Table(TableId::Table3) => ("TABLE3.PRG", "TABLE3.MOD"),
-                        Route::Table(TableId::Table4) => ("TABLE4.PRG", "TABLE4.MOD"),
-                    };
-                    #[cfg(not(target_arch = "wasm32"))]
-                    let prgdata = std::fs::read(g.game.args.data.join(prg)).unwrap();
                     #[cfg(not(target_arch = "wasm32"))]
-                    let moddata = std::fs::read(g.game.args.data.join(module)).unwrap();
-
-                    #[cfg(target_arch = "wasm32")]
-                    let prgdata = get_asset(
-                        g.game
-                            .args
-                            .data
-                            .join(prg)
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap(),
-                    );
-                    #[cfg(target_arch = "wasm32")]
-                    let moddata = get_asset(
-                        g.game
-                            .args
-                            .data
-                            .join(module)
-                            .file_name()
-                            .unwrap()
-                            .to_str()
-                            .unwrap(),
-                    );
-
-                    let view: Box<dyn View> = match route {
+                    save_trace(&mut g.game);
+                    let mut view: Box<dyn View> = match route {
                         Route::Intro(table) => {
-                            Box::new(Intro::new(&prgdata, &moddata, g.game.config, table))
+                            let module = if table.is_none() {
+                                "INTRO.MOD"
+                            } else {
+                                "MOD2.MOD"
+                            };
+                            let moddata = read_data(&g.game.args.data, module).unwrap();
+                            match g.game.bundle {
+                                Some(ref bundle) => Box::new(Intro::from_assets(
+                                    bundle.intro.clone(),
+                                    &moddata,
+                                    g.game.config,
+                                    table,
+                                )),
+                                None => {
+                                    let prgdata =
+                                        read_data(&g.game.args.data, "INTRO.PRG").unwrap();
+                                    Box::new(Intro::new(&prgdata, &moddata, g.game.config, table))
+                                }
+                            }
                         }
                         Route::Table(table) => {
-                            Box::new(Table::new(&prgdata, &moddata, g.game.config, table))
+                            let data = g.game.cache.get_or_load(table, || {
+                                load_table(&g.game.args.data, g.game.bundle.as_deref(), table)
+                            });
+                            #[cfg(not(target_arch = "wasm32"))]
+                            let lamps = g.game.args.lamps.and_then(|addr| {
+                                let map = load_lamp_map(&g.game.args.data, &data.assets);
+                                let protocol = match g.game.args.lamps_protocol {
+                                    LampFormat::Artnet => LampProtocol::ArtNet,
+                                    LampFormat::Raw => LampProtocol::Raw,
+                                };
+                                LampOutput::new(addr, protocol, map)
+                                    .map_err(|e| eprintln!("{addr}: {e}"))
+                                    .ok()
+                            });
+                            let mut view =
+                                Table::from_assets(data.assets, data.module, g.game.config);
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if let Some(lamps) = lamps {
+                                view.set_lamp_output(lamps);
+                            }
+                            if g.game.args.practice {
+                                view.set_practice(g.game.config.practice_shots[table]);
+                            }
+                            if g.game.args.debug {
+                                view.enable_debugger();
+                            }
+                            if g.game.args.demo {
+                                view.set_autoplay(AutoplayMode::Demo);
+                            }
+                            if g.game.args.flipper_assist || g.game.args.one_switch {
+                                view.set_assist(Assist {
+                                    flippers: g.game.args.flipper_assist,
+                                    one_switch: g.game.args.one_switch,
+                                });
+                            }
+                            if g.game.args.heatmap.is_some() {
+                                match g.game.traces[table].take() {
+                                    Some(trace) => view.resume_trace(trace),
+                                    None => view.set_trace(g.game.args.trajectory.is_some()),
+                                }
+                            }
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if let Some(ref events) = g.game.events {
+                                view.set_event_sink(Box::new(events.clone()));
+                            }
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if let Some(ref feedback) = g.game.feedback {
+                                view.set_feedback_sink(Box::new(feedback.clone()));
+                            }
+                            #[cfg(not(target_arch = "wasm32"))]
+                            {
+                                let mut sinks: Vec<Box<dyn DmSink>> = vec![];
+                                if let Some(ref dmd) = g.game.dmd {
+                                    sinks.push(Box::new(dmd.clone()));
+                                }
+                                if let Some(ref dmd_png) = g.game.dmd_png {
+                                    sinks.push(Box::new(dmd_png.clone()));
+                                }
+                                if !sinks.is_empty() {
+                                    let args = &g.game.args;
+                                    let layout = DmLayout {
+                                        width: args.dmd_size.0,
+                                        height: args.dmd_size.1,
+                                        scale: args.dmd_scale,
+                                        center: !args.dmd_top_left,
+                                    };
+                                    view.set_dm_output(layout, sinks);
+                                }
+                            }
+                            Box::new(view)
                         }
                     };
+                    view.set_turbo(g.game.turbo);
                     g.set_updates_per_second(view.get_fps());
                     g.game.view = Some(view);
                 }
-                Action::Exit => g.exit(),
+                Action::Exit => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    save_trace(&mut g.game);
+                    g.exit();
+                }
                 Action::SaveOptions(options) => {
                     options.save(&cstore);
                     g.game.config.options = options;
                 }
                 Action::SaveHighScores(table, high_scores) => {
-                    save_high_scores(table, high_scores, &cstore);
-                    g.game.config.high_scores[table] = high_scores;
+                    save_high_scores(table, &high_scores, &cstore);
+                    g.game.config.high_scores[table] = *high_scores;
                 }
+                Action::SavePracticeShots(table, shots) => {
+                    save_practice_shots(table, shots, &cstore);
+                    g.game.config.practice_shots[table] = shots;
+                }
+                Action::SaveStats(table, stats) => {
+                    save_stats(table, stats, &cstore);
+                    g.game.config.stats[table] = stats;
+                }
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(rpc) = &mut g.game.rpc {
+                let screen = (g.game.pixels.frame(), g.game.dims);
+                rpc.poll(g.game.view.as_deref_mut(), &g.game.config.options, screen);
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(input) = &mut g.game.input {
+                input.poll_view(g.game.view.as_deref_mut(), |e| eprintln!("input: {e}"));
             }
         },
         |g| {
@@ -319,6 +838,9 @@ async fn run(args: Args) {
                     frame[(offset + pidx) * 4 + 3] = 0xff;
                 }
             }
+            if let Some(ref view) = g.game.view {
+                view.render_overlay(&mut frame[offset * 4..], width);
+            }
             if g.game.args.touch {
                 for (i, x) in frame[..offset * 4].iter_mut().enumerate() {
                     if i % 4 == 3 {
@@ -364,6 +886,8 @@ async fn run(args: Args) {
                     event: WindowEvent::CloseRequested,
                     ..
                 } => {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    save_trace(&mut g.game);
                     g.exit();
                 }
                 Event::WindowEvent {
@@ -397,6 +921,14 @@ async fn run(args: Args) {
                                     g.window.set_fullscreen(Some(Fullscreen::Borderless(None)))
                                 }
                             }
+                            if key == KeyCode::Tab && *state == ElementState::Pressed {
+                                g.game.turbo = match g.game.turbo {
+                                    1 => 2,
+                                    2 => 4,
+                                    _ => 1,
+                                };
+                                view.set_turbo(g.game.turbo);
+                            }
                             view.handle_key(key, *state);
                         }
                     }
@@ -406,6 +938,9 @@ async fn run(args: Args) {
                     ..
                 } => {
                     if let Some(ref mut view) = g.game.view {
+                        if view.handle_mouse(g.game.cursor, *button, *state) {
+                            return;
+                        }
                         if &MouseButton::Left == button {
                             view.handle_key(KeyCode::ShiftLeft, *state);
                         }
@@ -414,6 +949,29 @@ async fn run(args: Args) {
                         }
                     }
                 }
+                Event::WindowEvent {
+                    event: WindowEvent::CursorMoved { position, .. },
+                    ..
+                } => {
+                    if let Some(ref view) = g.game.view {
+                        let pos = g
+                            .game
+                            .pixels
+                            .window_pos_to_pixel((position.x as f32, position.y as f32));
+                        let (x, mut y) = match pos {
+                            Ok((x, y)) => (x as i32, y as i32),
+                            Err((x, y)) => (x as i32, y as i32),
+                        };
+                        if g.game.args.touch {
+                            y -= if view.get_resolution().0 == 320 {
+                                40
+                            } else {
+                                80
+                            };
+                        }
+                        g.game.cursor = (x, y);
+                    }
+                }
                 Event::WindowEvent {
                     event: WindowEvent::Touch(touch),
                     ..
@@ -0,0 +1,450 @@
+//! Local control server.  Clients send JSON-RPC 2.0 requests, one per line, and get one
+//! response line back for each.  The server never blocks: the game polls it once a frame, and
+//! [`RpcControl`] carries out each [`Command`] on the current view.  Only loopback addresses
+//! are accepted, since clients can control the game and write screenshots.
+
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    path::{Component, Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::{
+    config::{Options, TableId},
+    view::{Route, View},
+};
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+const COMMAND_FAILED: i32 = -32000;
+
+// A client that lets this much output pile up isn't reading it.
+const MAX_PENDING: usize = 1 << 20;
+
+const METHODS: &[&str] = &[
+    "state",
+    "start_game",
+    "flipper",
+    "nudge",
+    "plunger",
+    "pause",
+    "navigate",
+    "screenshot",
+];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(
+    tag = "method",
+    content = "params",
+    rename_all = "snake_case",
+    deny_unknown_fields
+)]
+pub enum Command {
+    State {},
+    StartGame {
+        players: u8,
+    },
+    Flipper {
+        side: Side,
+        pressed: bool,
+    },
+    /// Holds the nudge key for `frames` frames.
+    Nudge {
+        frames: Option<u32>,
+    },
+    Plunger {
+        pressed: bool,
+    },
+    /// Toggles the pause menu.
+    Pause {},
+    /// Goes to a table, 1 to 4, or to the intro if `table` is missing.
+    Navigate {
+        table: Option<u8>,
+    },
+    /// Saves the last frame as a PNG, at `path` inside the screenshot directory.
+    Screenshot {
+        path: PathBuf,
+    },
+}
+
+struct Client {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    /// Responses not sent yet.
+    out: Vec<u8>,
+}
+
+pub struct RpcServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+}
+
+impl RpcServer {
+    /// Fails for addresses that aren't loopback ones.
+    pub fn bind(addr: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let addrs: Vec<_> = addr.to_socket_addrs()?.collect();
+        if let Some(addr) = addrs.iter().find(|addr| !addr.ip().is_loopback()) {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{} isn't a loopback address", addr.ip()),
+            ));
+        }
+        let listener = TcpListener::bind(&addrs[..])?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: vec![],
+        })
+    }
+
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts new clients and answers every complete request received so far.
+    pub fn poll(&mut self, mut handler: impl FnMut(Command) -> Result<Value, String>) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(Client {
+                    stream,
+                    buf: vec![],
+                    out: vec![],
+                });
+            }
+        }
+        self.clients
+            .retain_mut(|client| client.poll(&mut handler).is_ok());
+    }
+}
+
+/// An [`RpcServer`] driving the game.  Buttons are pressed as the keys they are bound to, and
+/// those pressed for a while are released after that many frames.
+pub struct RpcControl {
+    server: RpcServer,
+    /// Keys pressed by requests, and the frames left until they are released.
+    keys: Vec<(KeyCode, u32)>,
+    route: Option<Route>,
+    /// Where screenshots go.  Without it, they are refused.
+    screenshots: Option<PathBuf>,
+}
+
+impl RpcControl {
+    pub fn bind(addr: impl ToSocketAddrs, screenshots: Option<PathBuf>) -> std::io::Result<Self> {
+        Ok(Self {
+            server: RpcServer::bind(addr)?,
+            keys: vec![],
+            route: None,
+            screenshots,
+        })
+    }
+
+    /// Where a `navigate` request asked to go, once.
+    pub fn take_route(&mut self) -> Option<Route> {
+        self.route.take()
+    }
+
+    /// Releases the keys pressed by earlier requests that are due, then answers new requests.
+    /// `screen` is the window contents, as RGBA, and its size.
+    pub fn poll(
+        &mut self,
+        mut view: Option<&mut (dyn View + '_)>,
+        options: &Options,
+        screen: (&[u8], (u32, u32)),
+    ) {
+        let mut released = vec![];
+        self.keys.retain_mut(|(key, frames)| {
+            *frames -= 1;
+            if *frames == 0 {
+                released.push(*key);
+            }
+            *frames != 0
+        });
+        if let Some(view) = &mut view {
+            for key in released {
+                view.handle_key(key, ElementState::Released);
+            }
+        }
+        let Self {
+            server,
+            keys,
+            route,
+            screenshots,
+        } = self;
+        server.poll(|command| match command {
+            Command::Navigate { table } => {
+                *route = Some(match table {
+                    None => Route::Intro(None),
+                    Some(1) => Route::Table(TableId::Table1),
+                    Some(2) => Route::Table(TableId::Table2),
+                    Some(3) => Route::Table(TableId::Table3),
+                    Some(4) => Route::Table(TableId::Table4),
+                    Some(table) => return Err(format!("no table {table}")),
+                });
+                Ok(json!(true))
+            }
+            Command::Screenshot { path } => screenshot(screenshots.as_deref(), &path, screen),
+            command => run_command(command, view.as_deref_mut(), options, keys),
+        });
+    }
+}
+
+/// Writes the screen as a PNG to `path` inside `dir`.
+fn screenshot(
+    dir: Option<&Path>,
+    path: &Path,
+    (pixels, (width, height)): (&[u8], (u32, u32)),
+) -> Result<Value, String> {
+    let dir = dir.ok_or("screenshots are disabled")?;
+    let inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if !inside || path.as_os_str().is_empty() {
+        return Err(format!(
+            "{} isn't a path inside the screenshot directory",
+            path.display()
+        ));
+    }
+    let file = std::fs::File::create(dir.join(path)).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels))
+        .map_err(|e| e.to_string())?;
+    Ok(json!({ "width": width, "height": height }))
+}
+
+fn run_command(
+    command: Command,
+    view: Option<&mut (dyn View + '_)>,
+    options: &Options,
+    keys: &mut Vec<(KeyCode, u32)>,
+) -> Result<Value, String> {
+    let info = view.as_ref().and_then(|view| view.inspect());
+    if let Command::State {} = command {
+        return Ok(json!({
+            "view": if info.is_some() { "table" } else { "intro" },
+            "options": {
+                "balls": options.balls,
+                "angle_high": options.angle_high,
+                "scroll_speed": format!("{:?}", options.scroll_speed),
+                "resolution": format!("{:?}", options.resolution),
+                "no_music": options.no_music,
+                "mono": options.mono,
+                "game_speed": options.game_speed,
+            },
+            "game": info,
+        }));
+    }
+    let (Some(view), Some(info)) = (view, info) else {
+        return Err("not at a table".to_string());
+    };
+    // Keys to press, and for how many frames.  `None` leaves them pressed.
+    let (key, pressed, frames) = match command {
+        Command::StartGame { players } => {
+            let key = match players {
+                1 => KeyCode::F1,
+                2 => KeyCode::F2,
+                3 => KeyCode::F3,
+                4 => KeyCode::F4,
+                5 => KeyCode::F5,
+                6 => KeyCode::F6,
+                7 => KeyCode::F7,
+                8 => KeyCode::F8,
+                _ => return Err("players must be 1 to 8".to_string()),
+            };
+            if !info.in_attract {
+                return Err("a game is already running".to_string());
+            }
+            (key, true, Some(1))
+        }
+        Command::Flipper { side, pressed } => {
+            let key = match side {
+                Side::Left => KeyCode::ShiftLeft,
+                Side::Right => KeyCode::ShiftRight,
+            };
+            (key, pressed, None)
+        }
+        Command::Nudge { frames } => (KeyCode::Space, true, Some(frames.unwrap_or(10).max(1))),
+        Command::Plunger { pressed } => (KeyCode::ArrowDown, pressed, None),
+        Command::Pause {} => (KeyCode::KeyP, true, Some(1)),
+        Command::State {} | Command::Navigate { .. } | Command::Screenshot { .. } => {
+            unreachable!()
+        }
+    };
+    view.handle_key(
+        key,
+        if pressed {
+            ElementState::Pressed
+        } else {
+            ElementState::Released
+        },
+    );
+    if let Some(frames) = frames {
+        keys.push((key, frames));
+    }
+    Ok(json!(true))
+}
+
+impl Client {
+    /// Fails once the client is gone.
+    fn poll(
+        &mut self,
+        handler: &mut impl FnMut(Command) -> Result<Value, String>,
+    ) -> std::io::Result<()> {
+        let mut chunk = [0; 0x1000];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=end).collect();
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            serde_json::to_writer(&mut self.out, &handle_request(&line, handler)).unwrap();
+            self.out.push(b'\n');
+        }
+        while !self.out.is_empty() {
+            match self.stream.write(&self.out) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(n) => drop(self.out.drain(..n)),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        if self.out.len() > MAX_PENDING {
+            return Err(ErrorKind::OutOfMemory.into());
+        }
+        Ok(())
+    }
+}
+
+fn error(id: Value, code: i32, message: impl Into<String>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message.into() },
+    })
+}
+
+fn handle_request(
+    line: &[u8],
+    handler: &mut impl FnMut(Command) -> Result<Value, String>,
+) -> Value {
+    let request: Value = match serde_json::from_slice(line) {
+        Ok(request) => request,
+        Err(e) => return error(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return error(id, INVALID_REQUEST, "missing method");
+    };
+    let params = request.get("params").cloned().unwrap_or(json!({}));
+    let command = match serde_json::from_value(json!({ "method": method, "params": params })) {
+        Ok(command) => command,
+        Err(e) if METHODS.contains(&method) => return error(id, INVALID_PARAMS, e.to_string()),
+        Err(_) => return error(id, METHOD_NOT_FOUND, format!("unknown method {method}")),
+    };
+    match handler(command) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => error(id, COMMAND_FAILED, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        time::Duration,
+    };
+
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut server = RpcServer::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        client
+            .write_all(
+                concat!(
+                    r#"{"jsonrpc":"2.0","id":1,"method":"start_game","params":{"players":2}}"#,
+                    "\n",
+                    r#"{"jsonrpc":"2.0","id":2,"method":"flipper","params":{"side":"up"}}"#,
+                    "\n",
+                    r#"{"jsonrpc":"2.0","id":3,"method":"tilt"}"#,
+                    "\n",
+                    r#"{"jsonrpc":"2.0","id":4,"method":"state"}"#,
+                    "\n",
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        client
+            .set_read_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+        let mut reader = BufReader::new(client);
+        let mut line = String::new();
+        let mut commands = vec![];
+        let mut responses = vec![];
+        while responses.len() < 4 {
+            server.poll(|command| {
+                commands.push(command.clone());
+                match command {
+                    Command::State {} => Err("no table".to_string()),
+                    _ => Ok(json!(true)),
+                }
+            });
+            // Times out until the server has answered.
+            if reader.read_line(&mut line).is_ok() {
+                responses.push(serde_json::from_str::<Value>(&line).unwrap());
+                line.clear();
+            }
+        }
+        assert_eq!(
+            commands,
+            [Command::StartGame { players: 2 }, Command::State {}]
+        );
+        assert_eq!(responses[0]["result"], json!(true));
+        assert_eq!(responses[1]["error"]["code"], json!(INVALID_PARAMS));
+        assert_eq!(responses[2]["error"]["code"], json!(METHOD_NOT_FOUND));
+        assert_eq!(responses[3]["error"]["code"], json!(COMMAND_FAILED));
+    }
+
+    #[test]
+    fn loopback_only() {
+        assert!(RpcServer::bind("0.0.0.0:0").is_err());
+        assert!(RpcServer::bind("127.0.0.1:0").is_ok());
+    }
+
+    #[test]
+    fn screenshot_paths() {
+        let screen = (&[][..], (0, 0));
+        let dir = Path::new("shots");
+        for path in ["/tmp/x.png", "../x.png", "a/../../x.png", ""] {
+            assert!(
+                screenshot(Some(dir), Path::new(path), screen).is_err(),
+                "{path}"
+            );
+        }
+        assert!(screenshot(None, Path::new("x.png"), screen).is_err());
+    }
+}
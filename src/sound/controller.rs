@@ -9,6 +9,7 @@ pub struct Controller {
     volume: AtomicU32,
     sfx: AtomicU32,
     paused: AtomicBool,
+    tempo: AtomicU32,
 }
 
 impl Controller {
@@ -18,6 +19,7 @@ impl Controller {
             sfx: AtomicU32::new(0),
             volume: AtomicU32::new(0x100),
             paused: AtomicBool::new(false),
+            tempo: AtomicU32::new(100),
         }
     }
 
@@ -51,6 +53,16 @@ impl Controller {
         self.paused.load(Ordering::Relaxed)
     }
 
+    /// Plays the module at `percent` of its normal speed, at the same pitch.
+    pub fn set_tempo(&self, percent: u32) {
+        assert!(percent != 0);
+        self.tempo.store(percent, Ordering::Relaxed);
+    }
+
+    pub fn tempo(&self) -> u32 {
+        self.tempo.load(Ordering::Relaxed)
+    }
+
     pub fn play_sfx(&self, sfx: Sfx, volume: u8) {
         let val = (sfx.period as u32)
             | (sfx.sample as u32) << 8
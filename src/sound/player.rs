@@ -1,4 +1,7 @@
-use std::{ops::Deref, sync::Arc};
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
 
 use cpal::{
     traits::{DeviceTrait, HostTrait, StreamTrait},
@@ -16,7 +19,7 @@ const VIBRATO_LUT: [u8; 32] = [
 ];
 
 struct PlayerState {
-    module: Mod,
+    module: Arc<Mod>,
     sequencer: Arc<dyn Sequencer>,
     controller: Arc<Controller>,
     sample_rate: u32,
@@ -66,10 +69,24 @@ struct ChannelState {
 }
 
 pub struct Player {
-    _stream: Stream,
+    _stream: Option<Stream>,
+    /// Without a stream, the module is played along with the game instead.
+    silent: Option<Mutex<PlayerState>>,
     controller: Arc<Controller>,
 }
 
+impl Player {
+    /// Plays one frame's worth of a silent player's module, so that jingles end on time.  Does
+    /// nothing for players with sound.
+    pub fn advance_frame(&self, fps: u32) {
+        if let Some(state) = &self.silent {
+            let mut state = state.lock().unwrap();
+            let mut data = vec![0.0; (state.sample_rate / fps) as usize * 2];
+            state.make_samples(&mut data);
+        }
+    }
+}
+
 impl Deref for Player {
     type Target = Controller;
 
@@ -78,28 +95,21 @@ impl Deref for Player {
     }
 }
 
-pub fn play(module: Mod, sequencer: Option<Arc<dyn Sequencer>>) -> Player {
-    let host = cpal::default_host();
-    let device = host
-        .default_output_device()
-        .expect("no output device available");
-    /*let supported_configs_range = device
-        .supported_output_configs()
-        .expect("error while querying configs");
-    for cfg in supported_configs_range {
-        println!("{cfg:#?}");
-    }*/
-    let sample_rate = 48000;
+fn new_state(
+    module: Arc<Mod>,
+    sequencer: Option<Arc<dyn Sequencer>>,
+    controller: Arc<Controller>,
+    sample_rate: u32,
+) -> PlayerState {
     let sequencer = sequencer.unwrap_or_else(|| Arc::new(SimpleSequencer::new(&module)));
     let position = sequencer.next_position() as usize;
-    let controller = Arc::new(Controller::new());
-    let mut state = PlayerState {
+    PlayerState {
         module,
         speed: 6,
         ticks_left: 0,
         samples_left: 0,
         sequencer,
-        controller: controller.clone(),
+        controller,
         samples_in_tick: sample_rate / 50,
         position,
         row: 0,
@@ -126,7 +136,35 @@ pub fn play(module: Mod, sequencer: Option<Arc<dyn Sequencer>>) -> Player {
         sample_rate,
         pattern_break: None,
         jump: None,
-    };
+    }
+}
+
+/// Plays the module without a sound device.  It only moves on when told to, with
+/// [`Player::advance_frame`].
+pub fn play_silent(module: Arc<Mod>, sequencer: Option<Arc<dyn Sequencer>>) -> Player {
+    let controller = Arc::new(Controller::new());
+    let state = new_state(module, sequencer, controller.clone(), 48000);
+    Player {
+        _stream: None,
+        silent: Some(Mutex::new(state)),
+        controller,
+    }
+}
+
+pub fn play(module: Arc<Mod>, sequencer: Option<Arc<dyn Sequencer>>) -> Player {
+    let host = cpal::default_host();
+    let device = host
+        .default_output_device()
+        .expect("no output device available");
+    /*let supported_configs_range = device
+        .supported_output_configs()
+        .expect("error while querying configs");
+    for cfg in supported_configs_range {
+        println!("{cfg:#?}");
+    }*/
+    let sample_rate = 48000;
+    let controller = Arc::new(Controller::new());
+    let mut state = new_state(module, sequencer, controller.clone(), sample_rate);
 
     let config = StreamConfig {
         channels: 2,
@@ -149,7 +187,8 @@ pub fn play(module: Mod, sequencer: Option<Arc<dyn Sequencer>>) -> Player {
         .expect("failed to make stream");
     stream.play().unwrap();
     Player {
-        _stream: stream,
+        _stream: Some(stream),
+        silent: None,
         controller,
     }
 }
@@ -177,7 +216,7 @@ impl PlayerState {
                     self.ticks_left -= 1;
                     self.play_effects();
                 }
-                self.samples_left = self.samples_in_tick;
+                self.samples_left = self.samples_in_tick * 100 / self.controller.tempo();
                 self.controller.incr_tick();
             }
             data[pos] = ((self.play_channel(0) + self.play_channel(1)) / 0x100 * master_volume)
@@ -0,0 +1,89 @@
+//! Streaming output.  Game events, coils and dot matrix frames are written to a file (which
+//! can also be a serial port or a pipe) and sent to every connected TCP client.
+
+use std::{
+    fs::File,
+    io::Write,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    path::Path,
+};
+
+use crate::table::{Coil, DmFrame, DmSink, EventSink, FeedbackSink, GameEvent};
+
+/// Writes output to a file and to every connected TCP client, one complete message at a
+/// time.  Clients that can't keep up are dropped rather than holding up the game.
+pub struct StreamOutput {
+    name: &'static str,
+    file: Option<File>,
+    listener: Option<TcpListener>,
+    clients: Vec<TcpStream>,
+}
+
+impl StreamOutput {
+    /// `name` is used in messages about write errors.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            file: None,
+            listener: None,
+            clients: vec![],
+        }
+    }
+
+    pub fn create_file(&mut self, path: &Path) -> std::io::Result<()> {
+        self.file = Some(File::create(path)?);
+        Ok(())
+    }
+
+    pub fn listen(&mut self, addr: impl ToSocketAddrs) -> std::io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        self.listener = Some(listener);
+        Ok(())
+    }
+
+    pub fn send(&mut self, msg: &[u8]) {
+        if let Some(file) = &mut self.file {
+            if let Err(e) = file.write_all(msg) {
+                eprintln!("{}: {e}", self.name);
+                self.file = None;
+            }
+        }
+        if let Some(listener) = &self.listener {
+            while let Ok((client, _)) = listener.accept() {
+                if client.set_nonblocking(true).is_ok() {
+                    self.clients.push(client);
+                }
+            }
+        }
+        self.clients
+            .retain_mut(|client| client.write_all(msg).is_ok());
+    }
+}
+
+/// Game events, as JSON lines.
+impl EventSink for StreamOutput {
+    fn event(&mut self, frame: u64, event: &GameEvent) {
+        let mut line = vec![];
+        event.write_line(frame, &mut line).unwrap();
+        self.send(&line);
+    }
+}
+
+/// Coils, as `<coil> <strength>` lines.
+impl FeedbackSink for StreamOutput {
+    fn fire(&mut self, coil: Coil, strength: u8) {
+        let mut line = vec![];
+        coil.write_line(strength, &mut line).unwrap();
+        self.send(&line);
+    }
+}
+
+/// Dot matrix frames, in the raw protocol.
+impl DmSink for StreamOutput {
+    fn frame(&mut self, frame: &DmFrame) {
+        let mut packet = vec![];
+        frame.write_raw(&mut packet).unwrap();
+        self.send(&packet);
+    }
+}
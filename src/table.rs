@@ -3,8 +3,9 @@ use std::sync::Arc;
 use arrayvec::ArrayVec;
 use enum_map::{enum_map, EnumMap};
 use ndarray::Array2;
+use rand::{rngs::StdRng, SeedableRng};
 use unnamed_entity::EntityVec;
-use winit::event::{ElementState, TouchPhase};
+use winit::event::{ElementState, MouseButton, TouchPhase};
 use winit::keyboard::KeyCode;
 
 use crate::icons::IconKind;
@@ -18,23 +19,29 @@ use crate::{
         Assets,
     },
     bcd::Bcd,
-    config::{Config, HighScore, Options, Resolution, ScrollSpeed, TableId},
-    sound::{controller::TableSequencer, player::Player},
+    config::{Config, HighScoreTable, Options, Resolution, ScrollSpeed, TableId},
+    sound::{controller::TableSequencer, player::Player, Mod},
     view::{Action, Route, View},
 };
 
 use self::{
+    assist::AssistState,
+    autoplay::AutoplayState,
     ball::BallState,
+    breakdown::BreakdownState,
     cheat::CheatState,
-    dm::DotMatrix,
+    debug::DebugState,
+    events::EventsState,
     lights::Lights,
     party::PartyState,
     physics::{prep_materials, speed_fix, FlipperState, PushState},
     player::PlayerState,
+    practice::PracticeState,
     script::ScriptState,
     scroll::ScrollState,
     show::ShowState,
     speed::SpeedState,
+    stats::StatsState,
     stones::StonesState,
     tasks::{Task, TaskKind},
 };
@@ -42,9 +49,9 @@ use self::{
 pub struct Table {
     player: Player,
     sequencer: Arc<TableSequencer>,
-    assets: Assets,
+    assets: Arc<Assets>,
     options: Options,
-    high_scores: [HighScore; 4],
+    high_scores: HighScoreTable,
     hifps: bool,
     scroll: ScrollState,
     lights: Lights,
@@ -54,6 +61,7 @@ pub struct Table {
     script: ScriptState,
     tasks: Vec<Task>,
     ball: BallState,
+    rng: StdRng,
     cheat: CheatState,
     flippers: EntityVec<FlipperId, FlipperState>,
     physmaps: EnumMap<Layer, Array2<u8>>,
@@ -111,6 +119,23 @@ pub struct Table {
 
     quitting: bool,
     fade: u16,
+    /// Frames run for every frame shown, see [`View::set_turbo`].
+    turbo: u8,
+    /// Physics sub-steps owed to a slowed-down game, in hundredths.
+    game_speed_credit: u32,
+    debug_overlay: bool,
+    debug: DebugState,
+    practice: Option<PracticeState>,
+    stats: StatsState,
+    breakdown: BreakdownState,
+    trace: Option<Trace>,
+    events: Option<EventsState>,
+    lamps: Option<LampOutput>,
+    feedback: Option<Box<dyn FeedbackSink>>,
+    dm_output: Option<DmOutput>,
+    autoplay: Option<AutoplayState>,
+    assist: Option<AssistState>,
+    keep_records: bool,
 
     cur_player: u8,
     total_players: u8,
@@ -151,40 +176,88 @@ pub enum KbdState {
     Paused,
     PausedConfirmQuit,
     GetName,
+    PracticeName,
 }
 
+mod assist;
+mod autoplay;
 mod ball;
+mod breakdown;
 mod cheat;
+mod debug;
 mod dm;
+mod dm_output;
+mod events;
+mod feedback;
 mod flippers;
 mod game;
+mod inspect;
+mod lamps;
 mod lights;
 mod mode;
+mod overlay;
 mod party;
 mod physics;
 mod player;
+mod practice;
+#[cfg(test)]
+pub(crate) mod scenario;
 mod script;
 mod scroll;
 mod show;
 mod sound;
 mod speed;
+mod stats;
 mod stones;
 mod tasks;
+mod trace;
 mod triggers;
 
+pub use self::assist::Assist;
+pub use self::autoplay::{AutoplayMode, AutoplayReport, StuckBall};
+pub use self::breakdown::ScoreSource;
+pub use self::dm::{DotMatrix, ScriptTaskDmAnim};
+pub use self::dm_output::{DmFrame, DmLayout, DmOutput, DmPngDump, DmSink};
+pub use self::events::{EventSink, GameEvent};
+pub use self::feedback::{Coil, FeedbackSink, Kicker};
+pub use self::inspect::{BallInfo, TableInfo};
+pub use self::lamps::{LampMap, LampMapError, LampOutput, LampProtocol};
+pub use self::trace::{Trace, TraceSample, TrajectoryFormat};
+
 impl Table {
     pub fn new(prg: &[u8], module: &[u8], config: Config, table: TableId) -> Table {
+        Table::from_assets(
+            Arc::new(Assets::load(prg, table)),
+            Arc::new(crate::sound::loader::load(module)),
+            config,
+        )
+    }
+
+    pub fn from_assets(assets: Arc<Assets>, module: Arc<Mod>, config: Config) -> Table {
+        Self::with_player(assets, module, config, true)
+    }
+
+    /// A table without sound, which doesn't need an audio device and runs exactly as fast as
+    /// its frames are run.
+    pub fn from_assets_silent(assets: Arc<Assets>, module: Arc<Mod>, config: Config) -> Table {
+        Self::with_player(assets, module, config, false)
+    }
+
+    fn with_player(assets: Arc<Assets>, module: Arc<Mod>, config: Config, sound: bool) -> Table {
         let options = config.options;
-        let high_scores = config.high_scores[table];
-        let assets = Assets::load(prg, table);
-        let module = crate::sound::loader::load(module);
+        let high_scores = config.high_scores[assets.table];
+        let stats = StatsState::new(config.stats[assets.table]);
         let sequencer = Arc::new(TableSequencer::new(
             assets.jingle_binds[JingleBind::Attract].unwrap().position,
             assets.position_jingle_start,
             assets.jingle_binds[JingleBind::Silence].unwrap().position,
             options.no_music,
         ));
-        let player = crate::sound::player::play(module, Some(sequencer.clone()));
+        let player = if sound {
+            crate::sound::player::play(module, Some(sequencer.clone()))
+        } else {
+            crate::sound::player::play_silent(module, Some(sequencer.clone()))
+        };
 
         let hifps = false;
         let scroll = ScrollState::new(&options);
@@ -210,6 +283,7 @@ impl Table {
             script: ScriptState::new(),
             tasks: vec![],
             ball: BallState::new(hifps),
+            rng: StdRng::from_entropy(),
             cheat: CheatState::new(),
             flippers,
             physmaps,
@@ -276,6 +350,21 @@ impl Table {
             start_key: None,
             quitting: false,
             fade: 0x100,
+            turbo: 1,
+            game_speed_credit: 0,
+            debug_overlay: false,
+            debug: DebugState::default(),
+            practice: None,
+            stats,
+            breakdown: BreakdownState::default(),
+            trace: None,
+            events: None,
+            lamps: None,
+            feedback: None,
+            dm_output: None,
+            autoplay: None,
+            assist: None,
+            keep_records: true,
 
             cur_player: 1,
             total_players: 1,
@@ -311,9 +400,26 @@ impl Table {
         res.ball.set_pos((280, 525));
         res.start_script(ScriptBind::Init);
         res.flippers_physmap_update();
+        res.update_tempo();
         res
     }
 
+    /// Makes everything left to chance — the plunger's strength, skill shots, match digits,
+    /// prizes — come out the same every time for the same seed and the same input.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.ball.seed(seed.wrapping_add(1));
+    }
+
+    /// Keeps games out of the high score table and the statistics when `false`.
+    pub fn set_keep_records(&mut self, keep: bool) {
+        self.keep_records = keep;
+    }
+
+    fn game_on_record(&self) -> bool {
+        self.keep_records && !self.in_practice() && !self.in_autoplay()
+    }
+
     pub fn pause(&mut self) {
         self.dm.save();
         self.dm.clear();
@@ -348,6 +454,27 @@ impl Table {
         self.sequencer.set_no_music(self.options.no_music);
     }
 
+    pub fn pause_option_game_speed(&mut self) {
+        self.options.game_speed = if self.options.game_speed <= 50 {
+            100
+        } else {
+            self.options.game_speed - 10
+        };
+        self.update_tempo();
+        self.dm.clear();
+        let msg = format!("GAME SPEED {}", self.options.game_speed);
+        let x = (160 - msg.len() as i16 * 8) / 2;
+        self.dm_puts(DmFont::H13, DmCoord { x, y: 1 }, msg.as_bytes());
+        self.pause_cycle = 0;
+        self.option_changed = true;
+    }
+
+    /// The music follows both the game speed and turbo.
+    fn update_tempo(&self) {
+        self.player
+            .set_tempo(self.turbo as u32 * self.options.game_speed as u32);
+    }
+
     pub fn pause_option_angle(&mut self) {
         self.options.angle_high = !self.options.angle_high;
         self.dm.clear();
@@ -421,6 +548,79 @@ impl Table {
         self.dm_puts(DmFont::H13, DmCoord { x: 0, y: 1 }, b"REALLY QUIT (Y OR N)");
         self.kbd_state = KbdState::PausedConfirmQuit;
     }
+
+    fn set_flipper(&mut self, side: FlipperSide, pressed: bool) {
+        if pressed && self.flippers_enabled && !self.flipper_state[side] {
+            self.flipper_pressed = true;
+            self.play_sfx_bind(SfxBind::FlipperPress);
+        }
+        self.flipper_state[side] = pressed;
+    }
+
+    fn set_spring(&mut self, pressed: bool) {
+        self.spring_down_state = pressed;
+        if !pressed {
+            self.spring_released = true;
+        }
+    }
+
+    fn game_frame_start(&mut self) {
+        self.scroll.update(self.ball.pos().1);
+        if let Some(players) = self.start_key {
+            self.start_key = None;
+            self.total_players = players;
+            self.players = vec![PlayerState::new(self.assets.table); players as usize];
+            self.start_script(ScriptBind::GameStartPlayers);
+            self.play_sfx_bind(SfxBind::GameStart);
+            self.add_task(TaskKind::SetStartKeysActive);
+        }
+    }
+
+    /// Runs this frame's share of a slowed-down game: at 50%, half of the physics sub-steps.
+    /// Returns `true` once a whole frame's worth has been run, and the rest of the frame
+    /// (scripts, tasks, timers) is due.
+    fn game_speed_frame(&mut self) -> bool {
+        let speed = self.options.game_speed as u32;
+        if speed >= 100 {
+            return true;
+        }
+        let substeps = self.physics_substeps();
+        self.game_speed_credit += speed * substeps as u32;
+        if self.in_attract {
+            // No physics, the frame just comes less often.
+            if self.game_speed_credit < 100 * substeps as u32 {
+                return false;
+            }
+            self.game_speed_credit -= 100 * substeps as u32;
+            return true;
+        }
+        while self.game_speed_credit >= 100 && self.debug.substeps < substeps {
+            if self.debug.substeps == 0 {
+                self.game_frame_start();
+            }
+            self.physics_frame();
+            self.debug.substeps += 1;
+            self.game_speed_credit -= 100;
+        }
+        self.debug.substeps == substeps
+    }
+
+    fn physics_substeps(&self) -> u8 {
+        if self.cheat.slowdown {
+            3
+        } else {
+            4
+        }
+    }
+
+    /// Height of the visible part of the board, above the dot matrix display.
+    fn board_height(&self) -> usize {
+        match self.options.resolution {
+            Resolution::Normal => 240 - 33,
+            Resolution::High => 350 - 33,
+            Resolution::Full => 576,
+        }
+    }
 }
 
 impl View for Table {
@@ -488,18 +688,23 @@ impl View for Table {
             KbdState::GetName => [(6, IconKind::Fullscreen), (7, IconKind::Pause)]
                 .into_iter()
                 .collect(),
+            KbdState::PracticeName => [(6, IconKind::Fullscreen)].into_iter().collect(),
         }
     }
 
     fn run_frame(&mut self) -> Action {
-        if self.kbd_state == KbdState::Paused {
+        if !self.debug_frame_start() {
+            return Action::None;
+        }
+        self.player.advance_frame(self.get_fps());
+        let action = if self.kbd_state == KbdState::Paused {
             self.pause_cycle += 1;
             if self.pause_cycle == 120 {
                 self.dm.clear();
                 self.dm_puts(DmFont::H13, DmCoord { x: 32, y: 1 }, b"P TO UNPAUSE");
             } else if self.pause_cycle == 240 {
                 self.dm.clear();
-                self.dm_puts(DmFont::H13, DmCoord { x: 16, y: 1 }, b"ASMR FOR OPTIONS");
+                self.dm_puts(DmFont::H13, DmCoord { x: 12, y: 1 }, b"ASMRG FOR OPTIONS");
             } else if self.pause_cycle == 360 {
                 self.dm.clear();
                 self.dm_puts(DmFont::H13, DmCoord { x: 36, y: 1 }, b"GAME PAUSED");
@@ -511,7 +716,10 @@ impl View for Table {
             } else {
                 Action::None
             }
-        } else if self.kbd_state == KbdState::PausedConfirmQuit {
+        } else if matches!(
+            self.kbd_state,
+            KbdState::PausedConfirmQuit | KbdState::PracticeName
+        ) {
             Action::None
         } else if self.quitting {
             if self.fade != 0 {
@@ -523,7 +731,12 @@ impl View for Table {
             } else {
                 Action::None
             }
+        } else if !self.game_speed_frame() {
+            // Slowed down, and the rest of the frame isn't due yet.
+            Action::None
         } else {
+            self.autoplay_frame();
+            self.assist_frame();
             if self.in_attract {
                 self.scroll.attract_frame();
                 self.lights.attract_frame(&self.assets);
@@ -543,34 +756,27 @@ impl View for Table {
                         JingleBind::Plunger
                     }]
                     .unwrap();
-                    self.sequencer
-                        .play_jingle(jingle, true, Some(plunger.position));
+                    self.play_jingle(jingle, true, Some(plunger.position));
                     self.issue_ball();
                     self.add_task(TaskKind::SetStartKeysActive);
                 }
             } else {
-                self.scroll.update(self.ball.pos().1);
-                if let Some(players) = self.start_key {
-                    self.start_key = None;
-                    self.total_players = players;
-                    self.players = vec![PlayerState::new(self.assets.table); players as usize];
-                    self.start_script(ScriptBind::GameStartPlayers);
-                    self.play_sfx_bind(SfxBind::GameStart);
-                    self.add_task(TaskKind::SetStartKeysActive);
+                // The debugger or a reduced game speed may have already run the start of
+                // this frame and some or all of the physics sub-steps.
+                if self.debug.substeps == 0 {
+                    self.game_frame_start();
                 }
-                if !self.cheat.slowdown {
+                for _ in self.debug.substeps..self.physics_substeps() {
                     self.physics_frame();
                 }
-                self.physics_frame();
-                self.physics_frame();
-                self.physics_frame();
+                self.debug.substeps = 0;
                 if self.tilt_counter != 0 {
                     self.tilt_counter -= 1;
                 }
                 self.score_bumper();
                 self.ball_gravity();
                 self.check_transitions();
-                if self.drained && !self.in_drain {
+                if self.drained && !self.in_drain && !self.practice_drained() {
                     self.ball.teleport_freeze(Layer::Ground, (280, 525));
                     self.flippers_enabled = false;
                     self.in_mode = false;
@@ -578,6 +784,11 @@ impl View for Table {
                     self.in_mode_ramp = false;
                     if !self.block_drain {
                         self.in_drain = true;
+                        self.stat(|stats| stats.balls_drained += 1);
+                        self.emit(GameEvent::BallDrained {
+                            player: self.cur_player,
+                            ball: self.cur_ball,
+                        });
                         match self.assets.table {
                             TableId::Table1 => self.party_drained(),
                             TableId::Table2 => self.speed_drained(),
@@ -609,6 +820,9 @@ impl View for Table {
                         self.tilt_counter += 60;
                         if self.tilt_counter > 120 {
                             self.tilted = true;
+                            self.stat(|stats| stats.tilts += 1);
+                            self.emit(GameEvent::Tilt);
+                            self.fire(Coil::Tilt);
                             self.flippers_enabled = false;
                             self.play_jingle_bind_silence(JingleBind::Tilt);
                             self.start_script(ScriptBind::Tilt);
@@ -628,46 +842,58 @@ impl View for Table {
                 } else if self.spring_down_state && self.spring_pos < 0x20 {
                     self.spring_pos += 1;
                 }
+                self.stats_frame();
             }
             self.script_frame();
+            self.breakdown_frame();
+            self.events_frame();
             if self.flush_high_scores {
                 self.flush_high_scores = false;
-                Action::SaveHighScores(self.assets.table, self.high_scores)
+                Action::SaveHighScores(self.assets.table, Box::new(self.high_scores))
+            } else if let Some(action) = self.practice_flush() {
+                action
+            } else if let Some(action) = self.stats_flush() {
+                action
             } else if self.option_changed {
                 self.option_changed = false;
                 Action::SaveOptions(self.options)
             } else {
                 Action::None
             }
-        }
+        };
+        self.lamps_frame();
+        self.dm_output_frame();
+        self.debug_frame_end();
+        action
     }
 
     fn handle_key(&mut self, key: KeyCode, state: ElementState) {
+        if key == KeyCode::F12 && state == ElementState::Pressed {
+            self.debug_overlay = !self.debug_overlay;
+            return;
+        }
+        if state == ElementState::Pressed && self.handle_debug_key(key) {
+            return;
+        }
+        if self.autoplay_key(state) {
+            return;
+        }
+        if self.one_switch_key(key, state) {
+            return;
+        }
         if matches!(
             key,
             KeyCode::ShiftLeft | KeyCode::ControlLeft | KeyCode::AltLeft
         ) {
-            if state == ElementState::Pressed
-                && self.flippers_enabled
-                && !self.flipper_state[FlipperSide::Left]
-            {
-                self.flipper_pressed = true;
-                self.play_sfx_bind(SfxBind::FlipperPress);
-            }
-            self.flipper_state[FlipperSide::Left] = state == ElementState::Pressed;
+            self.assist_flipper_key(FlipperSide::Left);
+            self.set_flipper(FlipperSide::Left, state == ElementState::Pressed);
         }
         if matches!(
             key,
             KeyCode::ShiftRight | KeyCode::ControlRight | KeyCode::AltRight
         ) {
-            if state == ElementState::Pressed
-                && self.flippers_enabled
-                && !self.flipper_state[FlipperSide::Right]
-            {
-                self.flipper_pressed = true;
-                self.play_sfx_bind(SfxBind::FlipperPress);
-            }
-            self.flipper_state[FlipperSide::Right] = state == ElementState::Pressed;
+            self.assist_flipper_key(FlipperSide::Right);
+            self.set_flipper(FlipperSide::Right, state == ElementState::Pressed);
         }
 
         if key == KeyCode::Space {
@@ -678,10 +904,7 @@ impl View for Table {
         }
 
         if key == KeyCode::ArrowDown {
-            self.spring_down_state = state == ElementState::Pressed;
-            if state == ElementState::Released {
-                self.spring_released = true;
-            }
+            self.set_spring(state == ElementState::Pressed);
         }
 
         if state != ElementState::Pressed {
@@ -721,6 +944,9 @@ impl View for Table {
 
         match self.kbd_state {
             KbdState::Main => {
+                if !self.in_attract && !self.in_drain && self.handle_practice_key(key) {
+                    return;
+                }
                 if self.start_keys_active && (self.in_attract || self.at_spring) {
                     match key {
                         KeyCode::F1 | KeyCode::Digit1 => self.start_key = Some(1),
@@ -761,10 +987,6 @@ impl View for Table {
                             self.option_changed = true;
                         }
                         KeyCode::KeyP => self.pause(),
-                        // KeyCode::W => self.ball.speed = (0, -1000),
-                        // KeyCode::S => self.ball.speed = (0, 1000),
-                        // KeyCode::A => self.ball.speed = (-1000, 0),
-                        // KeyCode::D => self.ball.speed = (1000, 0),
                         _ => (),
                     }
                 }
@@ -782,6 +1004,7 @@ impl View for Table {
                 KeyCode::KeyR => self.pause_option_resolution(),
                 KeyCode::KeyS => self.pause_option_scrolling(),
                 KeyCode::KeyA => self.pause_option_angle(),
+                KeyCode::KeyG => self.pause_option_game_speed(),
                 KeyCode::KeyP => self.unpause(),
                 KeyCode::Escape => self.pause_confirm_quit(),
                 _ => (),
@@ -800,9 +1023,41 @@ impl View for Table {
                     let _ = self.name_buf.try_push(chr);
                 }
             }
+            KbdState::PracticeName => self.handle_practice_name_key(key, chr),
         }
     }
 
+    fn handle_mouse(&mut self, pos: (i32, i32), button: MouseButton, state: ElementState) -> bool {
+        self.handle_practice_mouse(pos, button, state)
+    }
+
+    fn handle_plunger(&mut self, pos: u8) {
+        self.spring_analog(pos);
+    }
+
+    fn set_turbo(&mut self, factor: u8) {
+        self.turbo = factor;
+        self.update_tempo();
+    }
+
+    fn render_overlay(&self, frame: &mut [u8], width: usize) {
+        if self.debug_overlay {
+            self.render_debug_overlay(frame, width);
+        }
+    }
+
+    fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    fn take_breakdown_report(&mut self) -> Option<String> {
+        Table::take_breakdown_report(self)
+    }
+
+    fn inspect(&self) -> Option<TableInfo> {
+        Some(self.info())
+    }
+
     fn render(&self, data: &mut [u8], pal: &mut [(u8, u8, u8)]) {
         pal.copy_from_slice(&self.assets.main_board.cmap);
         for (lid, light) in &self.assets.lights {
@@ -821,11 +1076,7 @@ impl View for Table {
         } else {
             self.assets.dm_palette.color_off
         };
-        let height = match self.options.resolution {
-            Resolution::Normal => 240 - 33,
-            Resolution::High => 350 - 33,
-            Resolution::Full => 576,
-        };
+        let height = self.board_height();
         let spring_pos = self.spring_pos as usize / 2;
         let (bx, mut by) = self.ball.pos();
         if !self.ball.frozen {
@@ -0,0 +1,123 @@
+//! Aids for players with limited motor control.  The flipper assist raises a flipper by itself
+//! whenever the ball comes at it.  In one-switch mode, a single button starts the game, works
+//! the plunger while the ball sits on it, and raises both flippers otherwise.
+
+use enum_map::EnumMap;
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::assets::table::flippers::FlipperSide;
+
+use super::{KbdState, Table};
+
+// Frames to look ahead along the ball's path, enough for the flipper to come up in time.
+const ASSIST_LOOKAHEAD: i16 = 4;
+const ASSIST_HOLD_FRAMES: u8 = 10;
+const ASSIST_REST_FRAMES: u8 = 8;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Assist {
+    /// Raise the flippers automatically.
+    pub flippers: bool,
+    /// Any flipper key, or the plunger key, does everything.
+    pub one_switch: bool,
+}
+
+#[derive(Debug)]
+pub struct AssistState {
+    assist: Assist,
+    /// Frames the assist has been holding each flipper for, 0 if it isn't.
+    held: EnumMap<FlipperSide, u8>,
+    rest: EnumMap<FlipperSide, u8>,
+    /// Whether the switch is pulling the plunger, rather than holding the flippers.
+    plunging: bool,
+}
+
+impl Table {
+    pub fn set_assist(&mut self, assist: Assist) {
+        self.assist = Some(AssistState {
+            assist,
+            held: EnumMap::default(),
+            rest: EnumMap::default(),
+            plunging: false,
+        });
+    }
+
+    /// A flipper key takes the flipper back from the assist.
+    pub(super) fn assist_flipper_key(&mut self, side: FlipperSide) {
+        if let Some(assist) = &mut self.assist {
+            assist.held[side] = 0;
+        }
+    }
+
+    /// Handles the switch in one-switch mode.  Returns `true` if the key was used up.
+    pub(super) fn one_switch_key(&mut self, key: KeyCode, state: ElementState) -> bool {
+        let in_autoplay = self.in_autoplay();
+        let Some(assist) = &mut self.assist else {
+            return false;
+        };
+        if !assist.assist.one_switch
+            || self.kbd_state != KbdState::Main
+            || in_autoplay
+            || !matches!(
+                key,
+                KeyCode::ShiftLeft
+                    | KeyCode::ControlLeft
+                    | KeyCode::AltLeft
+                    | KeyCode::ShiftRight
+                    | KeyCode::ControlRight
+                    | KeyCode::AltRight
+                    | KeyCode::ArrowDown
+            )
+        {
+            return false;
+        }
+        let pressed = state == ElementState::Pressed;
+        if pressed {
+            if self.in_attract {
+                if self.start_keys_active {
+                    self.start_key = Some(1);
+                }
+                return true;
+            }
+            assist.plunging = self.at_spring;
+            assist.held = EnumMap::default();
+        }
+        if assist.plunging {
+            self.set_spring(pressed);
+        } else {
+            self.set_flipper(FlipperSide::Left, pressed);
+            self.set_flipper(FlipperSide::Right, pressed);
+        }
+        true
+    }
+
+    pub(super) fn assist_frame(&mut self) {
+        let Some(assist) = &self.assist else {
+            return;
+        };
+        if !assist.assist.flippers || self.in_autoplay() {
+            return;
+        }
+        let approach = self.ball_approach(ASSIST_LOOKAHEAD);
+        let mut presses = vec![];
+        let assist = self.assist.as_mut().unwrap();
+        for (side, held) in &mut assist.held {
+            if *held != 0 {
+                *held += 1;
+                if *held > ASSIST_HOLD_FRAMES {
+                    *held = 0;
+                    assist.rest[side] = ASSIST_REST_FRAMES;
+                    presses.push((side, false));
+                }
+            } else if assist.rest[side] != 0 {
+                assist.rest[side] -= 1;
+            } else if approach[side].coming && self.flippers_enabled && !self.flipper_state[side] {
+                *held = 1;
+                presses.push((side, true));
+            }
+        }
+        for (side, pressed) in presses {
+            self.set_flipper(side, pressed);
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Autoplayer.  Watches the ball and works the flippers, the plunger and the nudge key the way
+//! a player would.  In demo mode, it starts a game once the table has been sitting in attract
+//! mode for a while, and gives the table back as soon as a key is pressed.  Otherwise it plays
+//! one game after another, which makes for a soak test.  Its games never reach the high score
+//! table or the statistics.
+
+use enum_map::EnumMap;
+use rand::Rng;
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::assets::table::{flippers::FlipperSide, physics::Layer};
+
+use super::{KbdState, Table};
+
+// Attract mode time before a demo game starts.
+const DEMO_IDLE_FRAMES: u32 = 60 * 30;
+// Attract mode time between games when playing all the time.
+const SOAK_IDLE_FRAMES: u32 = 60;
+const FLIP_HOLD_FRAMES: u16 = 10;
+const FLIP_REST_FRAMES: u8 = 8;
+const PLUNGER_WAIT_FRAMES: u16 = 40;
+// A ball that stays within this many pixels isn't moving.
+const STILL_DISTANCE: i16 = 3;
+const STUCK_NUDGE_FRAMES: u32 = 60 * 4;
+const STUCK_DRAIN_FRAMES: u32 = 60 * 20;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AutoplayMode {
+    /// Plays a demo game whenever the table has been in attract mode for a while.
+    Demo,
+    /// Plays one game after another.
+    Always,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AutoplayReport {
+    pub games: u64,
+    pub balls: u64,
+    /// Balls that didn't move for a long time, and were drained to get the game going again.
+    pub stuck: Vec<StuckBall>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct StuckBall {
+    pub layer: Layer,
+    pub pos: (i16, i16),
+}
+
+#[derive(Debug)]
+pub struct AutoplayState {
+    mode: AutoplayMode,
+    /// Whether the current game is the autoplayer's.
+    playing: bool,
+    /// Set while the autoplayer presses keys itself.
+    pressing: bool,
+    idle_frames: u32,
+    /// Frames each flipper has been held for, 0 if released.
+    held: EnumMap<FlipperSide, u16>,
+    rest: EnumMap<FlipperSide, u8>,
+    /// How far ahead to look for the ball, in frames.  Varied from flip to flip, so that the
+    /// shots go all over the table.
+    lookahead: i16,
+    /// How long to keep the ball cradled on a raised flipper.
+    cradle_frames: u16,
+    plunger_frames: u16,
+    plunger_pull: u16,
+    still_pos: (i16, i16),
+    still_frames: u32,
+    was_drained: bool,
+    report: AutoplayReport,
+}
+
+impl Table {
+    pub fn set_autoplay(&mut self, mode: AutoplayMode) {
+        self.autoplay = Some(AutoplayState {
+            mode,
+            playing: false,
+            pressing: false,
+            idle_frames: 0,
+            held: EnumMap::default(),
+            rest: EnumMap::default(),
+            lookahead: 4,
+            cradle_frames: 0,
+            plunger_frames: 0,
+            plunger_pull: 0,
+            still_pos: (0, 0),
+            still_frames: 0,
+            was_drained: false,
+            report: AutoplayReport::default(),
+        });
+    }
+
+    pub fn autoplay_report(&self) -> Option<&AutoplayReport> {
+        self.autoplay.as_ref().map(|auto| &auto.report)
+    }
+
+    pub(super) fn in_autoplay(&self) -> bool {
+        self.autoplay.as_ref().is_some_and(|auto| auto.playing)
+    }
+
+    fn autoplay_press(&mut self, key: KeyCode, pressed: bool) {
+        use crate::view::View;
+
+        self.autoplay.as_mut().unwrap().pressing = true;
+        self.handle_key(
+            key,
+            if pressed {
+                ElementState::Pressed
+            } else {
+                ElementState::Released
+            },
+        );
+        self.autoplay.as_mut().unwrap().pressing = false;
+    }
+
+    /// Handles a key pressed by the player.  Any key ends a demo game, and keeps the next one
+    /// from starting for a while.  Returns `true` if the key was used up.
+    pub(super) fn autoplay_key(&mut self, state: ElementState) -> bool {
+        let Some(auto) = &mut self.autoplay else {
+            return false;
+        };
+        if auto.pressing || auto.mode != AutoplayMode::Demo || state != ElementState::Pressed {
+            return false;
+        }
+        auto.idle_frames = 0;
+        if !auto.playing || self.in_attract {
+            return false;
+        }
+        auto.playing = false;
+        auto.held = EnumMap::default();
+        self.flipper_state = EnumMap::default();
+        self.spring_down_state = false;
+        self.space_state = false;
+        self.abort_game();
+        true
+    }
+
+    pub(super) fn autoplay_frame(&mut self) {
+        let Some(auto) = &mut self.autoplay else {
+            return;
+        };
+        if self.in_attract {
+            if auto.playing {
+                auto.playing = false;
+                auto.report.games += 1;
+            }
+            auto.idle_frames += 1;
+            let wait = match auto.mode {
+                AutoplayMode::Demo => DEMO_IDLE_FRAMES,
+                AutoplayMode::Always => SOAK_IDLE_FRAMES,
+            };
+            if auto.idle_frames >= wait
+                && self.start_keys_active
+                && self.kbd_state == KbdState::Main
+            {
+                auto.idle_frames = 0;
+                auto.playing = true;
+                auto.was_drained = false;
+                self.start_key = Some(1);
+            }
+            return;
+        }
+        if !auto.playing || self.kbd_state != KbdState::Main {
+            return;
+        }
+        if self.in_drain != auto.was_drained {
+            auto.was_drained = self.in_drain;
+            if self.in_drain {
+                auto.report.balls += 1;
+            }
+        }
+        self.autoplay_plunger();
+        self.autoplay_flippers();
+        self.autoplay_unstick();
+    }
+
+    fn autoplay_plunger(&mut self) {
+        let auto = self.autoplay.as_mut().unwrap();
+        if !self.at_spring {
+            if auto.plunger_frames != 0 {
+                auto.plunger_frames = 0;
+                if self.spring_down_state {
+                    self.autoplay_press(KeyCode::ArrowDown, false);
+                }
+            }
+            return;
+        }
+        auto.plunger_frames += 1;
+        if auto.plunger_frames == PLUNGER_WAIT_FRAMES {
+            // Anything from a dribble to a full pull.
+            auto.plunger_pull = self.rng.gen_range(8..=40);
+            self.autoplay_press(KeyCode::ArrowDown, true);
+        } else if auto.plunger_frames == PLUNGER_WAIT_FRAMES + auto.plunger_pull {
+            self.autoplay_press(KeyCode::ArrowDown, false);
+        } else if auto.plunger_frames > PLUNGER_WAIT_FRAMES * 2 + 40 {
+            // Not launched after all, maybe the flippers were off.  Try again.
+            auto.plunger_frames = 0;
+        }
+    }
+
+    fn autoplay_flippers(&mut self) {
+        let approach = self.ball_approach(self.autoplay.as_ref().unwrap().lookahead);
+        let mut presses = vec![];
+        let auto = self.autoplay.as_mut().unwrap();
+        for (side, held) in &mut auto.held {
+            if *held != 0 {
+                *held += 1;
+                // Keep a ball resting on the raised flipper for a while, then let it roll
+                // down and shoot it.
+                let cradle = approach[side].resting && *held < auto.cradle_frames;
+                if *held > FLIP_HOLD_FRAMES && !cradle {
+                    *held = 0;
+                    auto.rest[side] = FLIP_REST_FRAMES;
+                    presses.push((side, false));
+                }
+            } else if auto.rest[side] != 0 {
+                auto.rest[side] -= 1;
+            } else if approach[side].coming && self.flippers_enabled {
+                *held = 1;
+                presses.push((side, true));
+            }
+        }
+        if presses.iter().any(|&(_, pressed)| pressed) {
+            let rng = &mut self.rng;
+            auto.lookahead = rng.gen_range(2..=6);
+            auto.cradle_frames = if rng.gen_bool(0.5) {
+                0
+            } else {
+                rng.gen_range(30..=120)
+            };
+        }
+        for (side, pressed) in presses {
+            let key = match side {
+                FlipperSide::Left => KeyCode::ShiftLeft,
+                FlipperSide::Right => KeyCode::ShiftRight,
+            };
+            self.autoplay_press(key, pressed);
+        }
+    }
+
+    /// Nudges a ball that doesn't move, and drains it if that doesn't help.
+    fn autoplay_unstick(&mut self) {
+        let auto = self.autoplay.as_mut().unwrap();
+        if self.space_state {
+            self.autoplay_press(KeyCode::Space, false);
+            return;
+        }
+        let pos = self.ball.pos_center();
+        if self.ball.frozen
+            || self.at_spring
+            || self.in_drain
+            || (pos.0 - auto.still_pos.0).abs() > STILL_DISTANCE
+            || (pos.1 - auto.still_pos.1).abs() > STILL_DISTANCE
+        {
+            auto.still_pos = pos;
+            auto.still_frames = 0;
+            return;
+        }
+        auto.still_frames += 1;
+        if auto.still_frames >= STUCK_DRAIN_FRAMES {
+            auto.report.stuck.push(StuckBall {
+                layer: self.ball.layer,
+                pos,
+            });
+            auto.still_frames = 0;
+            self.ball.teleport(Layer::Ground, (152, 576), (0, 0));
+        } else if auto.still_frames.is_multiple_of(STUCK_NUDGE_FRAMES) && !self.tilted {
+            self.autoplay_press(KeyCode::Space, true);
+        }
+    }
+}
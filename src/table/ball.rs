@@ -1,9 +1,10 @@
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::assets::table::physics::Layer;
 
 use super::physics::speed_fix;
 
+#[derive(Debug)]
 pub struct BallState {
     pub layer: Layer,
     pub pos_hires: (i32, i32),
@@ -12,6 +13,7 @@ pub struct BallState {
     pub frozen: bool,
     pub rotation: i16,
     pub max_speed: i16,
+    rng: StdRng,
 }
 
 impl BallState {
@@ -24,9 +26,14 @@ impl BallState {
             frozen: true,
             rotation: 0,
             max_speed: speed_fix(4100, hifps),
+            rng: StdRng::from_entropy(),
         }
     }
 
+    pub fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
     pub fn pos(&self) -> (i16, i16) {
         (
             (self.pos_hires.0 >> 10) as i16,
@@ -57,7 +64,7 @@ impl BallState {
         self.set_pos(pos);
         self.speed = speed;
         self.frozen = false;
-        let random: i16 = rand::thread_rng().gen_range(0..0x400);
+        let random: i16 = self.rng.gen_range(0..0x400);
         if (random & 1) != 0 {
             self.rotation = -random;
         } else {
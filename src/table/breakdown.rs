@@ -0,0 +1,299 @@
+//! Score breakdown.  Every award is credited to the source it came from, per player and per
+//! ball.  After game over the totals scroll by on the dot matrix, and a full report is kept
+//! for the frontend to pick up.  A match only awards a replay, so it contributes no points.
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::{
+    assets::table::{dm::DmFont, script::DmCoord, script::EffectBind},
+    bcd::Bcd,
+};
+
+use super::{events::GameEvent, Table};
+
+// Frames each summary screen is held, and frames spent scrolling to the next one.
+const SUMMARY_HOLD: u16 = 90;
+const SUMMARY_SCROLL: u16 = 16;
+// Sources shown per player on the dot matrix.  The report lists all of them.
+const SUMMARY_TOP: usize = 3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize)]
+pub enum ScoreSource {
+    Bumper,
+    Effect(EffectBind),
+    Jackpot,
+    SkillShot,
+    /// Targets, lanes and ramps scored by the table code rather than through an effect.
+    Award(&'static str),
+    /// The mode hit and ramp part of the end-of-ball bonus.
+    Mode,
+    /// The rest of the end-of-ball bonus.
+    Bonus,
+}
+
+impl ScoreSource {
+    /// Upper case, at most 20 characters, so it fits on the dot matrix.
+    fn label(self) -> String {
+        let mut res = match self {
+            ScoreSource::Bumper => "BUMPERS".to_string(),
+            ScoreSource::Effect(bind) => {
+                let name = format!("{bind:?}");
+                let name = ["Party", "Speed", "Show", "Stones"]
+                    .into_iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .unwrap_or(&name);
+                let mut res = String::new();
+                let mut prev: Option<char> = None;
+                for c in name.chars() {
+                    if let Some(p) = prev {
+                        if c.is_ascii_uppercase() || (c.is_ascii_digit() && !p.is_ascii_digit()) {
+                            res.push(' ');
+                        }
+                    }
+                    res.push(c.to_ascii_uppercase());
+                    prev = Some(c);
+                }
+                res
+            }
+            ScoreSource::Jackpot => "JACKPOT".to_string(),
+            ScoreSource::SkillShot => "SKILL SHOT".to_string(),
+            ScoreSource::Award(name) => name.to_string(),
+            ScoreSource::Mode => "MODE BONUS".to_string(),
+            ScoreSource::Bonus => "BONUS".to_string(),
+        };
+        res.truncate(20);
+        res
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Credit {
+    player: u8,
+    ball: u8,
+    source: ScoreSource,
+    points: Bcd,
+}
+
+#[derive(Debug, Clone)]
+enum SummaryScreen {
+    Text(String),
+    Score(Bcd),
+}
+
+#[derive(Debug)]
+struct Summary {
+    screens: Vec<SummaryScreen>,
+    idx: usize,
+    timer: u16,
+}
+
+#[derive(Debug, Default)]
+pub struct BreakdownState {
+    /// `None` outside of a game.
+    credits: Option<Vec<Credit>>,
+    /// Mode bonus added this ball and not paid out yet.
+    pending_mode: u64,
+    summary: Option<Summary>,
+    /// The report of the last game, until it is taken.
+    report: Option<String>,
+}
+
+impl Table {
+    /// Records `points` added to the main score.  Points going into the bonus are credited
+    /// when the bonus is paid at the end of the ball.
+    pub fn credit(&mut self, source: ScoreSource, points: Bcd) {
+        if points == Bcd::ZERO {
+            return;
+        }
+        self.emit(GameEvent::Award {
+            source,
+            points: points.to_u64(),
+        });
+        let Some(credits) = &mut self.breakdown.credits else {
+            return;
+        };
+        let (player, ball) = (self.cur_player, self.cur_ball);
+        match credits
+            .iter_mut()
+            .find(|c| c.player == player && c.ball == ball && c.source == source)
+        {
+            Some(credit) => credit.points += points,
+            None => credits.push(Credit {
+                player,
+                ball,
+                source,
+                points,
+            }),
+        }
+    }
+
+    pub fn credit_mode_bonus(&mut self, points: Bcd) {
+        self.breakdown.pending_mode += points.to_u64();
+    }
+
+    /// Credits one tick of the bonus countdown.  The mode bonus is paid out first.
+    pub fn credit_bonus_tick(&mut self, delta: Bcd) {
+        let points = delta.to_u64();
+        if self.breakdown.pending_mode >= points {
+            self.breakdown.pending_mode -= points;
+            self.credit(ScoreSource::Mode, delta);
+        } else {
+            self.breakdown.pending_mode = 0;
+            self.credit(ScoreSource::Bonus, delta);
+        }
+    }
+
+    pub fn breakdown_next_ball(&mut self) {
+        self.breakdown.pending_mode = 0;
+    }
+
+    pub fn breakdown_game_start(&mut self) {
+        self.breakdown = BreakdownState {
+            credits: Some(Vec::new()),
+            report: self.breakdown.report.take(),
+            ..Default::default()
+        };
+    }
+
+    pub fn breakdown_game_abort(&mut self) {
+        self.breakdown = BreakdownState {
+            report: self.breakdown.report.take(),
+            ..Default::default()
+        };
+    }
+
+    /// Hands over the full report of the last game played to the end, if it wasn't taken yet.
+    pub fn take_breakdown_report(&mut self) -> Option<String> {
+        self.breakdown.report.take()
+    }
+
+    pub fn breakdown_game_over(&mut self) {
+        let Some(credits) = self.breakdown.credits.take() else {
+            return;
+        };
+        self.breakdown.report = Some(self.breakdown_report(&credits));
+        let mut screens = vec![SummaryScreen::Text("GAME SUMMARY".to_string())];
+        for player in 1..=self.total_players {
+            let score = self.players[player as usize - 1].score_main;
+            screens.push(SummaryScreen::Text(format!("PLAYER {player}")));
+            screens.push(SummaryScreen::Score(score));
+            for (ball, points) in ball_totals(&credits, player) {
+                screens.push(SummaryScreen::Text(format!("BALL {ball}")));
+                screens.push(SummaryScreen::Score(points));
+            }
+            for (source, points) in source_totals(&credits, player)
+                .into_iter()
+                .take(SUMMARY_TOP)
+            {
+                screens.push(SummaryScreen::Text(source.label()));
+                screens.push(SummaryScreen::Score(points));
+            }
+        }
+        self.breakdown.summary = Some(Summary {
+            screens,
+            idx: 0,
+            timer: 0,
+        });
+    }
+
+    fn breakdown_report(&self, credits: &[Credit]) -> String {
+        let mut res = String::new();
+        let _ = writeln!(
+            res,
+            "Score breakdown, {} player{}:",
+            self.total_players,
+            if self.total_players == 1 { "" } else { "s" }
+        );
+        for player in 1..=self.total_players {
+            let score = self.players[player as usize - 1].score_main.to_u64();
+            let _ = writeln!(res, "\nPlayer {player}: {score}");
+            for (ball, points) in ball_totals(credits, player) {
+                let _ = writeln!(res, "  Ball {ball:<17} {:>13}", points.to_u64());
+            }
+            for (source, points) in source_totals(credits, player) {
+                let points = points.to_u64();
+                let _ = writeln!(
+                    res,
+                    "  {:<22} {points:>13} {:>5.1}%",
+                    source.label(),
+                    points as f64 * 100.0 / score.max(1) as f64
+                );
+            }
+        }
+        res
+    }
+
+    /// Draws the summary over whatever the attract script put on the dot matrix.
+    pub(super) fn breakdown_frame(&mut self) {
+        let Some(summary) = &mut self.breakdown.summary else {
+            return;
+        };
+        summary.timer += 1;
+        if summary.timer == SUMMARY_HOLD + SUMMARY_SCROLL {
+            summary.timer = 0;
+            summary.idx += 1;
+        }
+        let (idx, timer) = (summary.idx, summary.timer);
+        let Some(cur) = summary.screens.get(idx).cloned() else {
+            self.breakdown.summary = None;
+            self.dm.clear();
+            return;
+        };
+        let next = summary.screens.get(idx + 1).cloned();
+        let cur = self.summary_render(&cur);
+        let shift = timer.saturating_sub(SUMMARY_HOLD) as usize;
+        let next = match next {
+            Some(next) if shift != 0 => self.summary_render(&next),
+            _ => [[false; 160]; 16],
+        };
+        for y in 0..16 {
+            self.dm.pixels[y] = if y + shift < 16 {
+                cur[y + shift]
+            } else {
+                next[y + shift - 16]
+            };
+        }
+        self.dm.stop_blink();
+    }
+
+    fn summary_render(&mut self, screen: &SummaryScreen) -> [[bool; 160]; 16] {
+        self.dm.clear();
+        match screen {
+            SummaryScreen::Text(msg) => {
+                let x = (160 - 8 * msg.len().min(20) as i16) / 2;
+                self.dm_puts(DmFont::H13, DmCoord { x, y: 1 }, msg.as_bytes());
+            }
+            &SummaryScreen::Score(score) => {
+                self.dm_put_bcd(DmFont::H13, DmCoord { x: 32, y: 1 }, score, true);
+            }
+        }
+        self.dm.pixels
+    }
+}
+
+fn ball_totals(credits: &[Credit], player: u8) -> Vec<(u8, Bcd)> {
+    let mut res: Vec<(u8, Bcd)> = Vec::new();
+    for credit in credits.iter().filter(|c| c.player == player) {
+        match res.iter_mut().find(|(ball, _)| *ball == credit.ball) {
+            Some((_, points)) => *points += credit.points,
+            None => res.push((credit.ball, credit.points)),
+        }
+    }
+    res.sort_by_key(|&(ball, _)| ball);
+    res
+}
+
+/// Totals per source, biggest first.
+fn source_totals(credits: &[Credit], player: u8) -> Vec<(ScoreSource, Bcd)> {
+    let mut res: Vec<(ScoreSource, Bcd)> = Vec::new();
+    for credit in credits.iter().filter(|c| c.player == player) {
+        match res.iter_mut().find(|(source, _)| *source == credit.source) {
+            Some((_, points)) => *points += credit.points,
+            None => res.push((credit.source, credit.points)),
+        }
+    }
+    res.sort_by_key(|&(_, points)| std::cmp::Reverse(points));
+    res
+}
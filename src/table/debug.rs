@@ -0,0 +1,184 @@
+//! The frame-stepping debugger, enabled with `--debug`.  F9 freezes the table, F10 then runs a
+//! single frame, `.` a single physics sub-step, and `,` dumps the table state to stderr
+//! again.  The state is also dumped after every step.
+
+use winit::keyboard::KeyCode;
+
+use crate::{bcd::Bcd, config::TableId};
+
+use super::{KbdState, Table};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum DebugStep {
+    Frame,
+    Physics,
+}
+
+#[derive(Debug, Default)]
+pub struct DebugState {
+    enabled: bool,
+    frozen: bool,
+    step: Option<DebugStep>,
+    /// Physics sub-steps of the current frame that have already been run, by the debugger or
+    /// at reduced game speed.
+    pub substeps: u8,
+    frame: u64,
+}
+
+fn fmt_bcd(val: Bcd) -> String {
+    String::from_utf8_lossy(&val.to_ascii())
+        .trim_start()
+        .to_string()
+}
+
+impl Table {
+    pub fn enable_debugger(&mut self) {
+        self.debug.enabled = true;
+    }
+
+    pub(super) fn handle_debug_key(&mut self, key: KeyCode) -> bool {
+        if !self.debug.enabled {
+            return false;
+        }
+        match key {
+            KeyCode::F9 => {
+                self.debug.frozen = !self.debug.frozen;
+                if self.debug.frozen {
+                    self.debug_dump();
+                } else {
+                    eprintln!("DEBUG: resumed");
+                }
+            }
+            KeyCode::F10 if self.debug.frozen => self.debug.step = Some(DebugStep::Frame),
+            KeyCode::Period if self.debug.frozen => self.debug.step = Some(DebugStep::Physics),
+            KeyCode::Comma if self.debug.frozen => self.debug_dump(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn in_game_frame(&self) -> bool {
+        !matches!(
+            self.kbd_state,
+            KbdState::Paused | KbdState::PausedConfirmQuit | KbdState::PracticeName
+        ) && !self.quitting
+            && !self.in_attract
+    }
+
+    /// Returns `false` if the frame should not run.
+    pub(super) fn debug_frame_start(&mut self) -> bool {
+        if !self.debug.frozen {
+            return true;
+        }
+        match self.debug.step.take() {
+            None => false,
+            Some(DebugStep::Frame) => true,
+            Some(DebugStep::Physics) => {
+                // Once all sub-steps are done (or outside of gameplay, where there is no
+                // physics), the rest of the frame is run instead.
+                if !self.in_game_frame() || self.debug.substeps == self.physics_substeps() {
+                    return true;
+                }
+                if self.debug.substeps == 0 {
+                    self.game_frame_start();
+                }
+                self.physics_frame();
+                self.debug.substeps += 1;
+                self.debug_dump();
+                false
+            }
+        }
+    }
+
+    pub(super) fn debug_frame_end(&mut self) {
+        self.debug.frame += 1;
+        if self.debug.frozen {
+            self.debug_dump();
+        }
+    }
+
+    fn debug_dump(&self) {
+        let pos = self.script.pos();
+        let mut out = format!(
+            "==== DEBUG frame {frame} physics sub-step {sub}/{num}\n",
+            frame = self.debug.frame,
+            sub = self.debug.substeps,
+            num = self.physics_substeps(),
+        );
+        out += &format!(
+            "script: next {pos}: {uop:?}\n{script:#?}\n",
+            uop = self.assets.scripts.get(pos),
+            script = self.script,
+        );
+        out += "tasks:\n";
+        for task in &self.tasks {
+            out += &format!(
+                "    {kind:?} {timer}/{delay}\n",
+                kind = task.kind(),
+                timer = task.timer(),
+                delay = task.kind().delay(self),
+            );
+        }
+        out += &format!(
+            "kbd_state: {:?}  attract: {}  game_start: {}  plunger: {}  at_spring: {}  drain: {}  drained: {}  tilted: {} ({})  flippers: {}\n",
+            self.kbd_state,
+            self.in_attract,
+            self.in_game_start,
+            self.in_plunger,
+            self.at_spring,
+            self.in_drain,
+            self.drained,
+            self.tilted,
+            self.tilt_counter,
+            self.flippers_enabled,
+        );
+        out += &format!(
+            "mode: {} hit: {} ramp: {}  pending: {} hit: {} ramp: {}  timeout: {}s {}f\n",
+            self.in_mode,
+            self.in_mode_hit,
+            self.in_mode_ramp,
+            self.pending_mode,
+            self.pending_mode_hit,
+            self.pending_mode_ramp,
+            self.mode_timeout_secs,
+            self.mode_timeout_frames,
+        );
+        out += &format!(
+            "sequencer: music {:02x} priority {} jingle playing: {}\n",
+            self.sequencer.music(),
+            self.sequencer.priority(),
+            self.sequencer.jingle_playing(),
+        );
+        out += &format!(
+            "player {}/{} ball {}/{} (+{})  score {} bonus {} x{}/{}  jackpot {}\n",
+            self.cur_player,
+            self.total_players,
+            self.cur_ball,
+            self.total_balls,
+            self.extra_balls,
+            fmt_bcd(self.score_main),
+            fmt_bcd(self.score_bonus),
+            self.bonus_mult_early,
+            self.bonus_mult_late,
+            fmt_bcd(self.score_jackpot),
+        );
+        out += &format!(
+            "hit_pos: {:?}  hit_bumper: {:?}  roll_trigger: {:?} (prev {:?})\n",
+            self.hit_pos, self.hit_bumper, self.roll_trigger, self.prev_roll_trigger,
+        );
+        out += &format!(
+            "{:#?}\n{:#?}\n{:#?}\n{:#?}\n",
+            self.ball, self.push, self.scroll, self.cheat
+        );
+        for (fid, flipper) in &self.flippers {
+            out += &format!("flipper {fid}: {flipper:?}\n");
+        }
+        out += &match self.assets.table {
+            TableId::Table1 => format!("{:#?}\n", self.party),
+            TableId::Table2 => format!("{:#?}\n", self.speed),
+            TableId::Table3 => format!("{:#?}\n", self.show),
+            TableId::Table4 => format!("{:#?}\n", self.stones),
+        };
+        eprint!("{out}");
+    }
+}
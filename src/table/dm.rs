@@ -1,7 +1,8 @@
 use crate::{
     assets::table::{
         dm::DmFont,
-        script::{special_chars, DmAnimFrameId, DmAnimId, DmCoord, MsgId},
+        script::{special_chars, DmAnimFrame, DmAnimFrameId, DmAnimId, DmCoord, MsgId},
+        Assets,
     },
     bcd::Bcd,
 };
@@ -72,6 +73,18 @@ impl DotMatrix {
     pub fn clear(&mut self) {
         self.pixels = [[false; 160]; 16];
     }
+
+    pub fn apply_anim_frame(&mut self, frame: &DmAnimFrame) {
+        for &(pos, state) in frame.iter() {
+            self.pixels[pos.y as usize][pos.x as usize] = state;
+        }
+    }
+}
+
+impl Default for DotMatrix {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Table {
@@ -230,10 +243,7 @@ impl Table {
     }
 
     pub fn dm_anim_frame(&mut self, frame: DmAnimFrameId) {
-        let frame = &self.assets.anim_frames[frame];
-        for &(pos, state) in frame.iter() {
-            self.dm.pixels[pos.y as usize][pos.x as usize] = state;
-        }
+        self.dm.apply_anim_frame(&self.assets.anim_frames[frame]);
     }
 }
 
@@ -246,22 +256,28 @@ pub struct ScriptTaskDmAnim {
 }
 
 impl ScriptTaskDmAnim {
-    pub fn new(table: &Table, anim: DmAnimId) -> Self {
+    pub fn new(assets: &Assets, anim: DmAnimId) -> Self {
         Self {
             anim,
             frame_idx: 0,
             delay: 1,
-            repeats: table.assets.anims[anim].repeats,
+            repeats: assets.anims[anim].repeats,
         }
     }
 
     pub fn run(&mut self, table: &mut Table) -> bool {
+        let assets = table.assets.clone();
+        self.step(&assets, &mut table.dm)
+    }
+
+    /// Advances the animation by one tick.  Returns `false` once it is over.
+    pub fn step(&mut self, assets: &Assets, dm: &mut DotMatrix) -> bool {
         assert_ne!(self.delay, 0);
         self.delay -= 1;
         if self.delay != 0 {
             return true;
         }
-        let anim = &table.assets.anims[self.anim];
+        let anim = &assets.anims[self.anim];
         let frame_idx = self.frame_idx;
         if self.frame_idx == anim.num_frames {
             assert_ne!(self.repeats, 0);
@@ -273,7 +289,7 @@ impl ScriptTaskDmAnim {
         }
         let (frame, delay) = anim.frames[frame_idx];
         self.frame_idx += 1;
-        table.dm_anim_frame(frame);
+        dm.apply_anim_frame(&assets.anim_frames[frame]);
         self.delay = delay;
         true
     }
@@ -0,0 +1,234 @@
+//! Dot matrix output for external displays.  Every frame, the dot matrix is scaled onto a
+//! display of the given size and handed to the registered [`DmSink`]s, which can stream it to
+//! a real panel or dump it to files.
+
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use super::Table;
+
+const DM_WIDTH: usize = 160;
+const DM_HEIGHT: usize = 16;
+
+/// Where the dot matrix goes on the external display.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DmLayout {
+    pub width: u16,
+    pub height: u16,
+    /// Display dots per dot matrix dot, or `None` for the largest scale that fits.
+    pub scale: Option<f32>,
+    /// Centers the dot matrix on the display, instead of putting it in the top left corner.
+    pub center: bool,
+}
+
+impl Default for DmLayout {
+    fn default() -> Self {
+        Self {
+            width: DM_WIDTH as u16,
+            height: DM_HEIGHT as u16,
+            scale: None,
+            center: true,
+        }
+    }
+}
+
+impl DmLayout {
+    /// For every display dot, the dot matrix dot it shows, if any.
+    fn mapping(&self) -> Vec<Option<(usize, usize)>> {
+        let scale = self.scale.unwrap_or_else(|| {
+            (self.width as f32 / DM_WIDTH as f32).min(self.height as f32 / DM_HEIGHT as f32)
+        });
+        let (ox, oy) = if self.center {
+            (
+                (self.width as f32 - DM_WIDTH as f32 * scale) / 2.0,
+                (self.height as f32 - DM_HEIGHT as f32 * scale) / 2.0,
+            )
+        } else {
+            (0.0, 0.0)
+        };
+        let mut res = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let sx = ((x as f32 + 0.5 - ox) / scale).floor();
+                let sy = ((y as f32 + 0.5 - oy) / scale).floor();
+                res.push(
+                    ((0.0..DM_WIDTH as f32).contains(&sx) && (0.0..DM_HEIGHT as f32).contains(&sy))
+                        .then_some((sx as usize, sy as usize)),
+                );
+            }
+        }
+        res
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DmFrame {
+    pub width: u16,
+    pub height: u16,
+    /// Row by row, 0xff for a lit dot and 0 for a dark one.  Dots are dark while the dot
+    /// matrix is blinked off.
+    pub dots: Vec<u8>,
+    pub color_on: (u8, u8, u8),
+    pub color_off: (u8, u8, u8),
+}
+
+impl DmFrame {
+    /// The raw protocol: `PFDM`, the width and height as big-endian 16-bit numbers, then the
+    /// dots.
+    pub fn write_raw(&self, mut w: impl Write) -> std::io::Result<()> {
+        let mut packet = Vec::with_capacity(8 + self.dots.len());
+        packet.extend(b"PFDM");
+        packet.extend(self.width.to_be_bytes());
+        packet.extend(self.height.to_be_bytes());
+        packet.extend(&self.dots);
+        w.write_all(&packet)
+    }
+
+    /// Writes the frame as an RGB PNG, in the table's dot matrix colors.
+    pub fn write_png(&self, w: impl Write) -> Result<(), png::EncodingError> {
+        let on = [self.color_on.0, self.color_on.1, self.color_on.2];
+        let off = [self.color_off.0, self.color_off.1, self.color_off.2];
+        let data: Vec<u8> = self
+            .dots
+            .iter()
+            .flat_map(|&dot| if dot != 0 { on } else { off })
+            .collect();
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)
+    }
+}
+
+pub trait DmSink {
+    fn frame(&mut self, frame: &DmFrame);
+}
+
+impl<T: DmSink> DmSink for Rc<RefCell<T>> {
+    fn frame(&mut self, frame: &DmFrame) {
+        self.borrow_mut().frame(frame);
+    }
+}
+
+/// Writes every dot matrix frame that differs from the previous one as a PNG, named after the
+/// number of frames since the game started.
+pub struct DmPngDump {
+    dir: PathBuf,
+    frame: u64,
+    last: Option<DmFrame>,
+}
+
+impl DmPngDump {
+    /// Creates `dir` if it doesn't exist yet.
+    pub fn new(dir: PathBuf) -> std::io::Result<Self> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            frame: 0,
+            last: None,
+        })
+    }
+}
+
+impl DmSink for DmPngDump {
+    fn frame(&mut self, frame: &DmFrame) {
+        self.frame += 1;
+        if self.last.as_ref() == Some(frame) {
+            return;
+        }
+        self.last = Some(frame.clone());
+        let path = self.dir.join(format!("dmd-{:07}.png", self.frame));
+        let res = File::create(&path)
+            .map_err(png::EncodingError::from)
+            .and_then(|file| frame.write_png(BufWriter::new(file)));
+        if let Err(e) = res {
+            eprintln!("{}: {e}", path.display());
+        }
+    }
+}
+
+pub struct DmOutput {
+    sinks: Vec<Box<dyn DmSink>>,
+    mapping: Vec<Option<(usize, usize)>>,
+    frame: DmFrame,
+}
+
+impl Table {
+    pub fn set_dm_output(&mut self, layout: DmLayout, sinks: Vec<Box<dyn DmSink>>) {
+        let palette = &self.assets.dm_palette;
+        self.dm_output = Some(DmOutput {
+            sinks,
+            mapping: layout.mapping(),
+            frame: DmFrame {
+                width: layout.width,
+                height: layout.height,
+                dots: vec![0; layout.width as usize * layout.height as usize],
+                color_on: palette.color_on,
+                color_off: self.assets.main_board.cmap[palette.index_off as usize],
+            },
+        });
+    }
+
+    pub(super) fn dm_output_frame(&mut self) {
+        let Some(output) = &mut self.dm_output else {
+            return;
+        };
+        let state = self.dm.state();
+        for (dot, &src) in output.frame.dots.iter_mut().zip(&output.mapping) {
+            let lit = src.is_some_and(|(x, y)| state && self.dm.pixels[y][x]);
+            *dot = if lit { 0xff } else { 0 };
+        }
+        for sink in &mut output.sinks {
+            sink.frame(&output.frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout() {
+        let lit = |layout: DmLayout| -> Vec<bool> {
+            layout.mapping().iter().map(Option::is_some).collect()
+        };
+        // 192×64 fits the dot matrix at 1.2 dots per dot, centered vertically.
+        let mapping = DmLayout {
+            width: 192,
+            height: 64,
+            ..Default::default()
+        }
+        .mapping();
+        assert_eq!(mapping[0], None);
+        assert_eq!(mapping[21 * 192], None);
+        assert_eq!(mapping[22 * 192], Some((0, 0)));
+        assert_eq!(mapping[22 * 192 + 191], Some((159, 0)));
+        assert_eq!(mapping[41 * 192], Some((0, 15)));
+        assert_eq!(mapping[42 * 192], None);
+        // Twice the size doesn't fit 128×32, so it is cropped on both sides.
+        let mapping = DmLayout {
+            width: 128,
+            height: 32,
+            scale: Some(2.0),
+            center: true,
+        }
+        .mapping();
+        assert_eq!(mapping[0], Some((48, 0)));
+        assert_eq!(mapping[127], Some((111, 0)));
+        let corner = lit(DmLayout {
+            width: 400,
+            height: 40,
+            scale: Some(2.0),
+            center: false,
+        });
+        assert!(corner[0] && corner[319] && !corner[320]);
+        assert!(corner[31 * 400] && !corner[32 * 400]);
+    }
+}
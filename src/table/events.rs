@@ -0,0 +1,202 @@
+//! Game event stream.  External tools register an [`EventSink`] with the table and get told
+//! about everything interesting that happens, one [`GameEvent`] at a time.  Things that change
+//! in many places (lights, modes, the score) are found by comparing frames.
+
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use serde::Serialize;
+
+use crate::{
+    assets::table::{
+        lights::LightId,
+        physics::{BumperId, HitTrigger, RollTrigger},
+        script::ScriptBind,
+        sound::{Jingle, Sfx},
+    },
+    bcd::Bcd,
+};
+
+use super::{breakdown::ScoreSource, Table};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    GameStart {
+        players: u8,
+    },
+    GameOver,
+    BallIssued {
+        player: u8,
+        ball: u8,
+    },
+    BallDrained {
+        player: u8,
+        ball: u8,
+    },
+    Tilt,
+    RollTrigger {
+        trigger: RollTrigger,
+    },
+    HitTrigger {
+        trigger: HitTrigger,
+    },
+    Bumper {
+        bumper: BumperId,
+    },
+    /// `played` is `false` if the effect's jingle or script lost to a higher priority one,
+    /// or was silenced because a mode or another effect is running.
+    Effect {
+        source: ScoreSource,
+        priority: u8,
+        silenced: bool,
+        played: bool,
+    },
+    Jingle {
+        position: u8,
+        priority: u8,
+        played: bool,
+    },
+    /// A dot matrix script started by the table code.  Effect scripts are reported as effects.
+    Script {
+        bind: ScriptBind,
+    },
+    Sfx {
+        sample: u8,
+        volume: u8,
+    },
+    Light {
+        light: LightId,
+        lit: bool,
+    },
+    ModeStart,
+    /// `completed` is `true` if a jackpot was collected during the mode, which is when the
+    /// statistics count it as completed.
+    ModeEnd {
+        completed: bool,
+    },
+    /// Points awarded to the current player, before any bonus multiplier.
+    Award {
+        source: ScoreSource,
+        points: u64,
+    },
+    Score {
+        player: u8,
+        score: u64,
+    },
+}
+
+impl GameEvent {
+    /// Writes the event as one line of JSON, with the frame it happened on alongside its
+    /// fields.
+    pub fn write_line(&self, frame: u64, mut w: impl Write) -> std::io::Result<()> {
+        #[derive(Serialize)]
+        struct Line<'a> {
+            frame: u64,
+            #[serde(flatten)]
+            event: &'a GameEvent,
+        }
+
+        let mut line = serde_json::to_vec(&Line { frame, event: self })?;
+        line.push(b'\n');
+        w.write_all(&line)
+    }
+}
+
+pub trait EventSink {
+    /// `frame` counts table frames since the sink was registered.
+    fn event(&mut self, frame: u64, event: &GameEvent);
+}
+
+impl<T: EventSink> EventSink for Rc<RefCell<T>> {
+    fn event(&mut self, frame: u64, event: &GameEvent) {
+        self.borrow_mut().event(frame, event);
+    }
+}
+
+pub struct EventsState {
+    sink: RefCell<Box<dyn EventSink>>,
+    frame: u64,
+    lights: Vec<bool>,
+    in_mode: bool,
+    player: u8,
+    score: Bcd,
+}
+
+impl Table {
+    pub fn set_event_sink(&mut self, sink: Box<dyn EventSink>) {
+        self.events = Some(EventsState {
+            sink: RefCell::new(sink),
+            frame: 0,
+            lights: self
+                .assets
+                .lights
+                .ids()
+                .map(|lid| self.lights.is_lit(lid))
+                .collect(),
+            in_mode: self.in_mode,
+            player: self.cur_player,
+            score: self.score_main,
+        });
+    }
+
+    pub fn emit(&self, event: GameEvent) {
+        if let Some(events) = &self.events {
+            events.sink.borrow_mut().event(events.frame, &event);
+        }
+    }
+
+    pub fn play_jingle(&self, jingle: Jingle, force: bool, music: Option<u8>) -> bool {
+        let played = self.sequencer.play_jingle(jingle, force, music);
+        self.emit(GameEvent::Jingle {
+            position: jingle.position,
+            priority: jingle.priority,
+            played,
+        });
+        played
+    }
+
+    pub fn play_sfx(&self, sfx: Sfx, volume: u8) {
+        self.player.play_sfx(sfx, volume);
+        self.emit(GameEvent::Sfx {
+            sample: sfx.sample,
+            volume,
+        });
+    }
+
+    pub(super) fn events_frame(&mut self) {
+        let mode_won = self.mode_won();
+        let Some(events) = &mut self.events else {
+            return;
+        };
+        let mut changes = vec![];
+        for (lid, prev) in self.assets.lights.ids().zip(&mut events.lights) {
+            let lit = self.lights.is_lit(lid);
+            if lit != *prev {
+                *prev = lit;
+                changes.push(GameEvent::Light { light: lid, lit });
+            }
+        }
+        if self.in_mode != events.in_mode {
+            events.in_mode = self.in_mode;
+            changes.push(if self.in_mode {
+                GameEvent::ModeStart
+            } else {
+                GameEvent::ModeEnd {
+                    completed: mode_won,
+                }
+            });
+        }
+        if self.cur_player != events.player || self.score_main != events.score {
+            events.player = self.cur_player;
+            events.score = self.score_main;
+            changes.push(GameEvent::Score {
+                player: self.cur_player,
+                score: self.score_main.to_u64(),
+            });
+        }
+        for event in changes {
+            self.emit(event);
+        }
+        self.events.as_mut().unwrap().frame += 1;
+    }
+}
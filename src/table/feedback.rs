@@ -0,0 +1,99 @@
+//! Force feedback for cabinets.  Whenever the original machine would have fired a coil — a
+//! flipper, a bumper, a kicker throwing the ball back out, the plunger, the tilt bob — the
+//! table tells the registered [`FeedbackSink`], which can drive contactors and shakers.
+
+use std::{cell::RefCell, fmt, io::Write, rc::Rc};
+
+use unnamed_entity::EntityId;
+
+use crate::assets::table::{flippers::FlipperSide, physics::BumperId};
+
+use super::Table;
+
+/// The holes a ball is kicked out of.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Kicker {
+    PartyDropZone,
+    PartySnacks,
+    PartyDemon,
+    SpeedPitStop,
+    ShowVault,
+    ShowCashpot,
+    StonesTower,
+    StonesWell,
+    StonesVault,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Coil {
+    Flipper(FlipperSide),
+    Bumper(BumperId),
+    Kicker(Kicker),
+    /// A new ball served to the plunger lane.
+    Trough,
+    Plunger,
+    Tilt,
+}
+
+/// Formats the coil as a single word, as used in the line protocol: `flipper_left`,
+/// `bumper_2`, `kicker_stones_tower`, `plunger`, …
+impl fmt::Display for Coil {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Coil::Flipper(FlipperSide::Left) => write!(f, "flipper_left"),
+            Coil::Flipper(FlipperSide::Right) => write!(f, "flipper_right"),
+            Coil::Bumper(bid) => write!(f, "bumper_{}", bid.to_idx()),
+            Coil::Kicker(kicker) => {
+                let name = match kicker {
+                    Kicker::PartyDropZone => "party_drop_zone",
+                    Kicker::PartySnacks => "party_snacks",
+                    Kicker::PartyDemon => "party_demon",
+                    Kicker::SpeedPitStop => "speed_pit_stop",
+                    Kicker::ShowVault => "show_vault",
+                    Kicker::ShowCashpot => "show_cashpot",
+                    Kicker::StonesTower => "stones_tower",
+                    Kicker::StonesWell => "stones_well",
+                    Kicker::StonesVault => "stones_vault",
+                };
+                write!(f, "kicker_{name}")
+            }
+            Coil::Trough => write!(f, "trough"),
+            Coil::Plunger => write!(f, "plunger"),
+            Coil::Tilt => write!(f, "tilt"),
+        }
+    }
+}
+
+impl Coil {
+    /// The line protocol: the coil and its strength, separated by a space.
+    pub fn write_line(self, strength: u8, mut w: impl Write) -> std::io::Result<()> {
+        writeln!(w, "{self} {strength}")
+    }
+}
+
+pub trait FeedbackSink {
+    /// `strength` is 255 for everything but the plunger, which goes by how far it was pulled.
+    fn fire(&mut self, coil: Coil, strength: u8);
+}
+
+impl<T: FeedbackSink> FeedbackSink for Rc<RefCell<T>> {
+    fn fire(&mut self, coil: Coil, strength: u8) {
+        self.borrow_mut().fire(coil, strength);
+    }
+}
+
+impl Table {
+    pub fn set_feedback_sink(&mut self, sink: Box<dyn FeedbackSink>) {
+        self.feedback = Some(sink);
+    }
+
+    pub(super) fn fire(&mut self, coil: Coil) {
+        self.fire_strength(coil, 0xff);
+    }
+
+    pub(super) fn fire_strength(&mut self, coil: Coil, strength: u8) {
+        if let Some(feedback) = &mut self.feedback {
+            feedback.fire(coil, strength);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::{
     assets::table::{
@@ -13,8 +13,8 @@ use crate::{
 };
 
 use super::{
-    party::PartyState, show::ShowState, speed::SpeedState, stones::StonesState, tasks::TaskKind,
-    KbdState, Table,
+    breakdown::ScoreSource, events::GameEvent, feedback::Coil, party::PartyState, show::ShowState,
+    speed::SpeedState, stones::StonesState, tasks::TaskKind, KbdState, Table,
 };
 
 impl Table {
@@ -36,6 +36,11 @@ impl Table {
         self.num_cyclone = 0;
         self.bcd_num_cyclone = Bcd::ZERO;
         self.score_cyclone_bonus = Bcd::ZERO;
+        self.stats_game_start();
+        self.breakdown_game_start();
+        self.emit(GameEvent::GameStart {
+            players: self.total_players,
+        });
     }
 
     pub fn reset_player_state(&mut self) {
@@ -78,7 +83,7 @@ impl Table {
                 self.stones = StonesState::new();
                 self.raise_physmap(PhysmapBind::StonesGateTowerEntry);
                 self.raise_physmap(PhysmapBind::StonesGateKickback);
-                let target = thread_rng().gen_range(0..3);
+                let target = self.rng.gen_range(0..3);
                 self.stones.key_skillshot = Some(target);
                 self.light_blink(LightBind::StonesKey, target, 1, 0)
             }
@@ -124,6 +129,10 @@ impl Table {
     }
 
     pub fn issue_ball(&mut self) {
+        self.emit(GameEvent::BallIssued {
+            player: self.cur_player,
+            ball: self.cur_ball,
+        });
         self.in_drain = false;
         self.drained = false;
         self.in_plunger = true;
@@ -157,9 +166,12 @@ impl Table {
     pub fn issue_ball_release(&mut self) {
         self.ball
             .teleport(Layer::Ground, self.assets.issue_ball_release_pos, (10, 0));
+        self.fire(Coil::Trough);
     }
 
     pub fn abort_game(&mut self) {
+        self.stats_game_abort();
+        self.breakdown_game_abort();
         self.ball.teleport(Layer::Ground, (300, 570), (0, 0));
         self.kbd_state = KbdState::Main;
         self.add_task(TaskKind::GameOver);
@@ -168,14 +180,16 @@ impl Table {
         self.start_script(ScriptBind::Attract);
     }
 
-    pub fn score(&mut self, main: Bcd, bonus: Bcd) {
+    pub fn score(&mut self, source: ScoreSource, main: Bcd, bonus: Bcd) {
+        self.credit(source, main);
         self.score_main += main;
         self.score_bonus += bonus;
         self.ball_scored_points = true;
         self.reset_idle();
     }
 
-    pub fn score_premult(&mut self, main: Bcd, bonus: Bcd) {
+    pub fn score_premult(&mut self, source: ScoreSource, main: Bcd, bonus: Bcd) {
+        self.credit(source, main);
         self.score_main += main;
         for _ in 0..self.bonus_mult_early {
             self.score_bonus += bonus;
@@ -184,21 +198,29 @@ impl Table {
         self.reset_idle();
     }
 
-    pub fn effect_force_raw(&mut self, effect: Effect) {
-        match effect.sound {
+    pub fn effect_force_raw(&mut self, source: ScoreSource, effect: Effect) {
+        let priority = match effect.sound {
             EffectSound::Jingle(jingle) => {
-                self.sequencer.play_jingle(jingle, true, None);
+                self.play_jingle(jingle, true, None);
+                jingle.priority
             }
-            EffectSound::Silent(_) => (),
+            EffectSound::Silent(priority) => priority,
         };
-        self.score(effect.score_main, effect.score_bonus);
+        self.emit(GameEvent::Effect {
+            source,
+            priority,
+            silenced: false,
+            played: true,
+        });
+        self.score(source, effect.score_main, effect.score_bonus);
         if let Some(script) = effect.script {
             self.start_script_raw(script);
         }
     }
 
-    pub fn effect_raw(&mut self, effect: Effect) -> bool {
-        let present = match effect.sound {
+    pub fn effect_raw(&mut self, source: ScoreSource, effect: Effect) -> bool {
+        let mut silenced = false;
+        let (present, priority) = match effect.sound {
             EffectSound::Jingle(jingle) => {
                 if (self.silence_effect || self.in_mode)
                     && jingle.position
@@ -206,14 +228,21 @@ impl Table {
                             .unwrap()
                             .position
                 {
-                    false
+                    silenced = true;
+                    (false, jingle.priority)
                 } else {
-                    self.sequencer.play_jingle(jingle, false, None)
+                    (self.play_jingle(jingle, false, None), jingle.priority)
                 }
             }
-            EffectSound::Silent(priority) => priority >= self.sequencer.priority(),
+            EffectSound::Silent(priority) => (priority >= self.sequencer.priority(), priority),
         };
-        self.score(effect.score_main, effect.score_bonus);
+        self.emit(GameEvent::Effect {
+            source,
+            priority,
+            silenced,
+            played: present,
+        });
+        self.score(source, effect.score_main, effect.score_bonus);
         if present {
             if let Some(script) = effect.script {
                 self.start_script_raw(script);
@@ -223,11 +252,17 @@ impl Table {
     }
 
     pub fn effect_force(&mut self, effect: EffectBind) {
-        self.effect_force_raw(self.assets.effects[effect].unwrap());
+        self.effect_force_raw(
+            ScoreSource::Effect(effect),
+            self.assets.effects[effect].unwrap(),
+        );
     }
 
     pub fn effect(&mut self, effect: EffectBind) -> bool {
-        self.effect_raw(self.assets.effects[effect].unwrap())
+        self.effect_raw(
+            ScoreSource::Effect(effect),
+            self.assets.effects[effect].unwrap(),
+        )
     }
 
     pub fn enter(&mut self) {
@@ -239,8 +274,7 @@ impl Table {
             JingleBind::Main
         }]
         .unwrap();
-        self.sequencer
-            .play_jingle(jingle, true, Some(jingle.position));
+        self.play_jingle(jingle, true, Some(jingle.position));
         self.start_script(ScriptBind::Main);
         self.in_plunger = false;
         self.at_spring = false;
@@ -254,6 +288,7 @@ impl Table {
 
     pub fn extra_ball(&mut self) {
         self.extra_balls += 1;
+        self.stat(|stats| stats.extra_balls += 1);
         match self.assets.table {
             TableId::Table1 => {
                 self.light_set(LightBind::PartyExtraBall, 0, true);
@@ -340,6 +375,7 @@ impl ScriptTaskAccBonus {
         let mut delta = Bcd::ZERO;
         delta.digits[self.digit] = 1;
         table.score_main += delta;
+        table.credit_bonus_tick(delta);
         table.play_sfx_bind(SfxBind::TickBonus);
         table.dm_put_bcd(DmFont::H8, DmCoord { x: -32, y: 6 }, self.score, false);
         table.dm_put_bcd(
@@ -382,7 +418,7 @@ impl ScriptTaskMatch {
             },
             b"_",
         );
-        let mut new_digit = thread_rng().gen_range(0..10);
+        let mut new_digit = table.rng.gen_range(0..10);
         if new_digit == self.digit {
             new_digit += 1;
             if new_digit == 10 {
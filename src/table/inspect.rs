@@ -0,0 +1,98 @@
+//! A snapshot of the game state for external tools.
+
+use serde::Serialize;
+
+use crate::{
+    assets::table::{flippers::FlipperSide, physics::Layer},
+    config::TableId,
+};
+
+use super::{KbdState, Table};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TableInfo {
+    pub table: TableId,
+    pub in_attract: bool,
+    pub paused: bool,
+    pub tilted: bool,
+    pub in_mode: bool,
+    pub cur_player: u8,
+    pub total_players: u8,
+    pub cur_ball: u8,
+    pub total_balls: u8,
+    pub extra_balls: u8,
+    /// Main scores of all players in the game, or in the last one while in attract mode.
+    pub scores: Vec<u64>,
+    /// The current player's bonus, before the end-of-ball multiplier.
+    pub bonus: u64,
+    pub ball: BallInfo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BallInfo {
+    pub layer: Layer,
+    pub pos_hires: (i32, i32),
+    pub speed: (i16, i16),
+    pub frozen: bool,
+    pub in_plunger: bool,
+    pub drained: bool,
+}
+
+impl Table {
+    pub fn info(&self) -> TableInfo {
+        let scores = (1..=self.players.len() as u8)
+            .map(|player| {
+                if player == self.cur_player && !self.in_attract {
+                    self.score_main
+                } else {
+                    self.players[player as usize - 1].score_main
+                }
+            })
+            .map(|score| score.to_u64())
+            .collect();
+        TableInfo {
+            table: self.assets.table,
+            in_attract: self.in_attract,
+            paused: matches!(
+                self.kbd_state,
+                KbdState::Paused | KbdState::PausedConfirmQuit
+            ),
+            tilted: self.tilted,
+            in_mode: self.in_mode,
+            cur_player: self.cur_player,
+            total_players: self.total_players,
+            cur_ball: self.cur_ball,
+            total_balls: self.total_balls,
+            extra_balls: self.extra_balls,
+            scores,
+            bonus: self.score_bonus.to_u64(),
+            ball: BallInfo {
+                layer: self.ball.layer,
+                pos_hires: self.ball.pos_hires,
+                speed: self.ball.speed,
+                frozen: self.ball.frozen,
+                in_plunger: self.in_plunger,
+                drained: self.drained,
+            },
+        }
+    }
+
+    /// The position of every flipper, as the index of the graphic it is drawn with: 0 at rest,
+    /// counting up as it is raised.
+    pub fn flipper_quantums(&self) -> Vec<(FlipperSide, u16)> {
+        self.assets
+            .flippers
+            .iter()
+            .map(|(fid, flipper)| (flipper.side, self.flippers[fid].quantum))
+            .collect()
+    }
+
+    /// Whether each light is lit, by light number.
+    pub fn lights_lit(&self) -> Vec<bool> {
+        self.assets
+            .lights
+            .ids()
+            .map(|light| self.lights.is_lit(light))
+            .collect()
+    }
+}
@@ -0,0 +1,292 @@
+//! Lamp output for physical cabinets.  The state of every light is sent as DMX channel levels
+//! over UDP, either as Art-Net packets or as raw universes, whenever it changes and about once
+//! a second otherwise.
+//!
+//! Which light drives which channel comes from a mapping file, one light group per line:
+//!
+//! ```text
+//! # bind     index  universe  channel  [level]
+//! PartyPuke  0      0         1
+//! PartyPuke  *      0         10       128
+//! light      17     1         1
+//! ```
+//!
+//! `*` maps all lights of a group to consecutive channels.  `light` names a light by its
+//! number, for lights that aren't in any group.  Lit lamps are set to `level`, 255 by default.
+
+use std::{
+    collections::BTreeMap,
+    io,
+    net::{SocketAddr, UdpSocket},
+};
+
+use enum_map::Enum;
+use unnamed_entity::EntityId;
+
+use crate::assets::table::{
+    lights::{LightBind, LightId},
+    Assets,
+};
+
+use super::Table;
+
+// Frames between packets when nothing changes.  Art-Net nodes drop to black after a few
+// seconds without one.
+const KEEPALIVE_FRAMES: u32 = 60;
+
+#[derive(Clone, Debug)]
+pub struct LampMapError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl std::fmt::Display for LampMapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl std::error::Error for LampMapError {}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Lamp {
+    light: LightId,
+    universe: u16,
+    /// 0-based.
+    channel: u16,
+    level: u8,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LampMap {
+    lamps: Vec<Lamp>,
+}
+
+impl LampMap {
+    /// Every light on universe 0, group by group, starting at channel 1.  Lights that
+    /// aren't in any group come last.
+    pub fn new(assets: &Assets) -> Self {
+        let mut order: Vec<LightId> = vec![];
+        for lights in assets.light_binds.values() {
+            for &light in lights {
+                if !order.contains(&light) {
+                    order.push(light);
+                }
+            }
+        }
+        for light in assets.lights.ids() {
+            if !order.contains(&light) {
+                order.push(light);
+            }
+        }
+        Self {
+            lamps: order
+                .into_iter()
+                .enumerate()
+                .map(|(channel, light)| Lamp {
+                    light,
+                    universe: 0,
+                    channel: channel as u16,
+                    level: 0xff,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn parse(src: &str, assets: &Assets) -> Result<Self, LampMapError> {
+        let mut res = Self::default();
+        for (i, line) in src.lines().enumerate() {
+            let err = |msg: String| LampMapError { line: i + 1, msg };
+            let line = line.split('#').next().unwrap();
+            let words: Vec<_> = line.split_whitespace().collect();
+            if words.is_empty() {
+                continue;
+            }
+            if !(4..=5).contains(&words.len()) {
+                return Err(err(
+                    "expected bind, index, universe, channel and level".into()
+                ));
+            }
+            let num = |word: &str, max: u32| match word.parse::<u32>() {
+                Ok(val) if val <= max => Ok(val),
+                _ => Err(err(format!("bad number {word}"))),
+            };
+            let universe = num(words[2], 0x7fff)? as u16;
+            let channel = num(words[3], 512)? as u16;
+            if channel == 0 {
+                return Err(err("channels start at 1".into()));
+            }
+            let level = match words.get(4) {
+                Some(word) => num(word, 0xff)? as u8,
+                None => 0xff,
+            };
+            let lights: Vec<LightId> = if words[0] == "light" {
+                let idx = num(words[1], assets.lights.len() as u32 - 1)?;
+                vec![LightId::from_idx(idx as usize)]
+            } else {
+                let Some(bind) = (0..LightBind::LENGTH)
+                    .map(LightBind::from_usize)
+                    .find(|bind| format!("{bind:?}") == words[0])
+                else {
+                    return Err(err(format!("unknown light group {}", words[0])));
+                };
+                let group = &assets.light_binds[bind];
+                if words[1] == "*" {
+                    group.clone()
+                } else {
+                    let idx = num(words[1], u32::MAX)? as usize;
+                    let Some(&light) = group.get(idx) else {
+                        return Err(err(format!("{} has no light {idx}", words[0])));
+                    };
+                    vec![light]
+                }
+            };
+            for (j, light) in lights.into_iter().enumerate() {
+                let channel = channel - 1 + j as u16;
+                if channel >= 512 {
+                    return Err(err("runs past channel 512".into()));
+                }
+                res.lamps.push(Lamp {
+                    light,
+                    universe,
+                    channel,
+                    level,
+                });
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum LampProtocol {
+    ArtNet,
+    /// The universe number, big-endian, followed by its 512 channels.
+    Raw,
+}
+
+pub struct LampOutput {
+    socket: UdpSocket,
+    target: SocketAddr,
+    protocol: LampProtocol,
+    map: LampMap,
+    universes: BTreeMap<u16, [u8; 512]>,
+    sequence: u8,
+    idle_frames: u32,
+}
+
+impl LampOutput {
+    pub fn new(target: SocketAddr, protocol: LampProtocol, map: LampMap) -> io::Result<Self> {
+        let bind: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind)?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            target,
+            protocol,
+            universes: map
+                .lamps
+                .iter()
+                .map(|lamp| (lamp.universe, [0; 512]))
+                .collect(),
+            map,
+            sequence: 0,
+            // Send the first frame right away.
+            idle_frames: KEEPALIVE_FRAMES,
+        })
+    }
+
+    /// Sends the lamp states if they changed since the last call, or if it's time for a
+    /// keepalive.
+    pub fn update(&mut self, is_lit: impl Fn(LightId) -> bool) {
+        let mut changed = false;
+        for lamp in &self.map.lamps {
+            let level = if is_lit(lamp.light) { lamp.level } else { 0 };
+            let data = self.universes.get_mut(&lamp.universe).unwrap();
+            changed |= data[lamp.channel as usize] != level;
+            data[lamp.channel as usize] = level;
+        }
+        self.idle_frames += 1;
+        if !changed && self.idle_frames < KEEPALIVE_FRAMES {
+            return;
+        }
+        self.idle_frames = 0;
+        // Art-Net uses 1-255, 0 means sequencing is off.
+        self.sequence = self.sequence % 0xff + 1;
+        for (&universe, data) in &self.universes {
+            let mut packet = vec![];
+            match self.protocol {
+                LampProtocol::ArtNet => {
+                    packet.extend(b"Art-Net\0");
+                    // OpDmx, protocol version 14.
+                    packet.extend([0x00, 0x50, 0, 14]);
+                    packet.extend([self.sequence, 0]);
+                    packet.extend([universe as u8, (universe >> 8) as u8]);
+                    packet.extend(512u16.to_be_bytes());
+                }
+                LampProtocol::Raw => packet.extend(universe.to_be_bytes()),
+            }
+            packet.extend(data);
+            // Lamps are best effort; a missed packet is fixed by the next one.
+            let _ = self.socket.send_to(&packet, self.target);
+        }
+    }
+}
+
+impl Table {
+    pub fn set_lamp_output(&mut self, lamps: LampOutput) {
+        self.lamps = Some(lamps);
+    }
+
+    pub(super) fn lamps_frame(&mut self) {
+        if let Some(lamps) = &mut self.lamps {
+            lamps.update(|light| self.lights.is_lit(light));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn art_net() {
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let lamp = |light, channel| Lamp {
+            light: LightId::from_idx(light),
+            universe: 0x102,
+            channel,
+            level: 0xff,
+        };
+        let map = LampMap {
+            lamps: vec![lamp(0, 0), lamp(1, 1), lamp(2, 511)],
+        };
+        let mut output =
+            LampOutput::new(listener.local_addr().unwrap(), LampProtocol::ArtNet, map).unwrap();
+        output.update(|light| light.to_idx() != 1);
+        let mut buf = [0; 1024];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(len, 18 + 512);
+        assert_eq!(&buf[..8], b"Art-Net\0");
+        assert_eq!(buf[8..12], [0x00, 0x50, 0, 14]);
+        assert_eq!(buf[14..18], [0x02, 0x01, 0x02, 0x00]);
+        assert_eq!(buf[18..21], [0xff, 0, 0]);
+        assert_eq!(buf[18 + 511], 0xff);
+        // Nothing changed, so nothing is sent until the keepalive.
+        output.update(|light| light.to_idx() != 1);
+        output.update(|light| light.to_idx() == 1);
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(len, 18 + 512);
+        assert_eq!(buf[18..21], [0, 0xff, 0]);
+    }
+}
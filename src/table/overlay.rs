@@ -0,0 +1,234 @@
+//! The physics debug overlay, drawn in RGB on top of the rendered frame.
+
+use crate::assets::table::{dm::DmFont, physics::Rect};
+
+use super::Table;
+
+type Color = (u8, u8, u8);
+
+const MATERIAL_COLORS: [Color; 8] = [
+    (0xff, 0x00, 0x00),
+    (0xff, 0x80, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x00, 0xff, 0x00),
+    (0x00, 0xff, 0xff),
+    (0x00, 0x00, 0xff),
+    (0xff, 0x00, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+const RAMP_COLORS: [Color; 15] = [
+    (0x40, 0x40, 0xc0),
+    (0x40, 0xc0, 0x40),
+    (0xc0, 0x40, 0x40),
+    (0x40, 0xc0, 0xc0),
+    (0xc0, 0x40, 0xc0),
+    (0xc0, 0xc0, 0x40),
+    (0x80, 0x80, 0xff),
+    (0x80, 0xff, 0x80),
+    (0xff, 0x80, 0x80),
+    (0x80, 0xff, 0xff),
+    (0xff, 0x80, 0xff),
+    (0xff, 0xff, 0x80),
+    (0x20, 0x80, 0x80),
+    (0x80, 0x20, 0x80),
+    (0x80, 0x80, 0x20),
+];
+
+const COLOR_ROLL_TRIGGER: Color = (0x00, 0xff, 0x00);
+const COLOR_HIT_TRIGGER: Color = (0xff, 0xff, 0x00);
+const COLOR_BUMPER: Color = (0xff, 0x40, 0x40);
+const COLOR_TRANSITION_UP: Color = (0x40, 0xc0, 0xff);
+const COLOR_TRANSITION_DOWN: Color = (0xc0, 0x40, 0xff);
+const COLOR_BALL: Color = (0xff, 0xff, 0xff);
+const COLOR_HIT: Color = (0xff, 0x00, 0x00);
+
+struct Canvas<'a> {
+    frame: &'a mut [u8],
+    width: usize,
+    height: usize,
+    // Board coordinates of the top left screen pixel.
+    origin: (i16, i16),
+}
+
+impl Canvas<'_> {
+    fn blend(&mut self, pos: (i16, i16), color: Color, alpha: u16) {
+        let x = pos.0 - self.origin.0;
+        let y = pos.1 - self.origin.1;
+        if x < 0 || x as usize >= self.width || y < 0 || y as usize >= self.height {
+            return;
+        }
+        let idx = (y as usize * self.width + x as usize) * 4;
+        for (i, c) in [color.0, color.1, color.2].into_iter().enumerate() {
+            let old = self.frame[idx + i] as u16;
+            self.frame[idx + i] = ((old * (0x100 - alpha) + c as u16 * alpha) >> 8) as u8;
+        }
+    }
+
+    fn plot(&mut self, pos: (i16, i16), color: Color) {
+        self.blend(pos, color, 0x100);
+    }
+
+    fn line(&mut self, from: (i16, i16), to: (i16, i16), color: Color) {
+        let steps = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+        for i in 0..=steps {
+            self.plot(
+                (
+                    from.0 + (to.0 - from.0) * i / steps,
+                    from.1 + (to.1 - from.1) * i / steps,
+                ),
+                color,
+            );
+        }
+    }
+
+    fn rect(&mut self, rect: Rect, color: Color) {
+        let (x0, y0) = rect.xy_min;
+        let (x1, y1) = rect.xy_max;
+        self.line((x0, y0), (x1, y0), color);
+        self.line((x0, y1), (x1, y1), color);
+        self.line((x0, y0), (x0, y1), color);
+        self.line((x1, y0), (x1, y1), color);
+    }
+}
+
+impl Table {
+    /// Draws a label with the smallest dot matrix font, which only has upper case letters
+    /// and digits.  Characters it doesn't have are skipped.
+    fn overlay_text(&self, canvas: &mut Canvas, mut pos: (i16, i16), text: &str, color: Color) {
+        let font = &self.assets.dm_fonts[DmFont::H5];
+        for chr in text.bytes() {
+            let Some(glyph) = font.get(&chr.to_ascii_uppercase()) else {
+                pos.0 += 3;
+                continue;
+            };
+            let width = glyph
+                .iter()
+                .map(|&line| 8 - line.trailing_zeros() as i16)
+                .max()
+                .unwrap_or(0);
+            for (y, &line) in glyph.iter().enumerate() {
+                for x in 0..8 {
+                    if line << x & 0x80 != 0 {
+                        canvas.plot((pos.0 + x, pos.1 + y as i16), color);
+                    }
+                }
+            }
+            pos.0 += width.max(2) + 1;
+        }
+    }
+
+    pub(super) fn render_debug_overlay(&self, frame: &mut [u8], width: usize) {
+        let layer = self.ball.layer;
+        let push = self.push.offset();
+        let mut canvas = Canvas {
+            frame,
+            width,
+            height: self.board_height(),
+            origin: (0, self.scroll.pos() as i16 + push),
+        };
+
+        // Physmap: walls by material, everything else by ramp.
+        let physmap = &self.physmaps[layer];
+        let mut ramp_extents: [Option<Rect>; 15] = [None; 15];
+        for y in 0..576 {
+            for x in 0..320 {
+                let byte = physmap[(x, y)];
+                let pos = (x as i16, y as i16);
+                if byte & 2 != 0 {
+                    canvas.blend(pos, MATERIAL_COLORS[byte as usize & 7], 0x80);
+                } else if byte >> 4 != 0xf {
+                    let ramp = (byte >> 4) as usize;
+                    canvas.blend(pos, RAMP_COLORS[ramp], 0x30);
+                    let extent = ramp_extents[ramp].get_or_insert(Rect {
+                        xy_min: pos,
+                        xy_max: pos,
+                    });
+                    extent.xy_min.0 = extent.xy_min.0.min(pos.0);
+                    extent.xy_min.1 = extent.xy_min.1.min(pos.1);
+                    extent.xy_max.0 = extent.xy_max.0.max(pos.0);
+                    extent.xy_max.1 = extent.xy_max.1.max(pos.1);
+                }
+            }
+        }
+        for (ramp, extent) in ramp_extents.iter().enumerate() {
+            if let Some(extent) = extent {
+                let center = (
+                    (extent.xy_min.0 + extent.xy_max.0) / 2,
+                    (extent.xy_min.1 + extent.xy_max.1) / 2,
+                );
+                self.overlay_text(&mut canvas, center, &ramp.to_string(), RAMP_COLORS[ramp]);
+            }
+        }
+
+        // Trigger areas.
+        let labelled = |canvas: &mut Canvas, rect: Rect, label: String, color: Color| {
+            canvas.rect(rect, color);
+            self.overlay_text(
+                canvas,
+                (rect.xy_min.0 + 1, rect.xy_min.1 + 1),
+                &label,
+                color,
+            );
+        };
+        for area in &self.assets.roll_triggers[layer] {
+            labelled(
+                &mut canvas,
+                area.rect,
+                format!("{:?}", area.kind),
+                COLOR_ROLL_TRIGGER,
+            );
+        }
+        for area in &self.assets.hit_triggers {
+            labelled(
+                &mut canvas,
+                area.rect,
+                format!("{:?}", area.kind),
+                COLOR_HIT_TRIGGER,
+            );
+        }
+        for (id, bumper) in &self.assets.bumpers {
+            let kind = if bumper.is_kicker { "KICKER" } else { "BUMPER" };
+            labelled(
+                &mut canvas,
+                bumper.rect,
+                format!("{kind}{id}"),
+                COLOR_BUMPER,
+            );
+        }
+        for &rect in &self.assets.transitions_up {
+            labelled(&mut canvas, rect, "UP".into(), COLOR_TRANSITION_UP);
+        }
+        for &rect in &self.assets.transitions_down {
+            labelled(&mut canvas, rect, "DOWN".into(), COLOR_TRANSITION_DOWN);
+        }
+
+        // The ball: speed vector from its center, and spin as a bar above it.  Like the ball
+        // itself, these move with the table when it is pushed, unless the ball is held.
+        let ball_push = if self.ball.frozen { 0 } else { push };
+        if !self.in_attract {
+            let (cx, mut cy) = self.ball.pos_center();
+            cy += ball_push;
+            let (vx, vy) = self.ball.speed;
+            canvas.line((cx, cy), (cx + (vx >> 6), cy + (vy >> 6)), COLOR_BALL);
+            let spin = (self.ball.rotation >> 4).clamp(-32, 32);
+            canvas.line((cx, cy - 10), (cx + spin, cy - 10), COLOR_BALL);
+        }
+        if let Some((x, mut y)) = self.hit_pos {
+            y += ball_push;
+            canvas.line((x - 3, y - 3), (x + 3, y + 3), COLOR_HIT);
+            canvas.line((x - 3, y + 3), (x + 3, y - 3), COLOR_HIT);
+        }
+
+        // Readout in the top left corner, in screen coordinates.
+        let origin = canvas.origin;
+        let (bx, by) = self.ball.pos();
+        let text = format!(
+            "{layer:?} X {bx} Y {by} VX {vx} VY {vy} ROT {rot}",
+            vx = self.ball.speed.0,
+            vy = self.ball.speed.1,
+            rot = self.ball.rotation,
+        );
+        self.overlay_text(&mut canvas, (origin.0 + 2, origin.1 + 2), &text, COLOR_BALL);
+    }
+}
@@ -1,4 +1,4 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::{
     assets::table::{
@@ -10,8 +10,9 @@ use crate::{
     bcd::Bcd,
 };
 
-use super::{tasks::TaskKind, Table};
+use super::{breakdown::ScoreSource, tasks::TaskKind, Table};
 
+#[derive(Debug)]
 pub struct PartyState {
     pub flipper_lock_puke: bool,
 
@@ -322,7 +323,11 @@ impl Table {
         self.light_set(LightBind::PartyDuckDrop, which, false);
         self.add_task(TaskKind::PartyDuckDrop(which));
         self.play_sfx_bind(SfxBind::PartyHitDuck);
-        self.score_premult(Bcd::from_ascii(b"7510"), Bcd::from_ascii(b"750"));
+        self.score_premult(
+            ScoreSource::Award("DUCK"),
+            Bcd::from_ascii(b"7510"),
+            Bcd::from_ascii(b"750"),
+        );
         self.mode_count_hit();
         if self.light_all_unlit(LightBind::PartyDuckDrop) {
             self.effect(EffectBind::PartyDuckAll);
@@ -469,6 +474,7 @@ impl Table {
             self.incr_jackpot();
             self.party.timeout_skill_shot = 0;
             self.party.score_tunnel_skill_shot += Bcd::from_ascii(b"1000000");
+            self.credit(ScoreSource::SkillShot, self.party.score_tunnel_skill_shot);
             self.score_main += self.party.score_tunnel_skill_shot;
             self.effect(EffectBind::PartyTunnelSkillShot);
             self.silence_effect = true;
@@ -528,7 +534,7 @@ impl Table {
     }
 
     pub fn party_arcade_pick_reward(&mut self) {
-        let delay = match thread_rng().gen_range(0..6) {
+        let delay = match self.rng.gen_range(0..6) {
             0 => {
                 // side extra ball
                 self.light_set(LightBind::PartySideExtraBall, 0, true);
@@ -585,7 +591,11 @@ impl Table {
             return;
         }
         self.party.in_snack = true;
-        self.score_premult(Bcd::from_ascii(b"50000"), Bcd::from_ascii(b"5000"));
+        self.score_premult(
+            ScoreSource::Award("SNACK RAMP"),
+            Bcd::from_ascii(b"50000"),
+            Bcd::from_ascii(b"5000"),
+        );
         self.mode_count_ramp();
         if self.party.snack_lit[2] {
             self.effect(EffectBind::PartySnack2);
@@ -671,8 +681,10 @@ impl Table {
             } else {
                 self.start_script(ScriptBind::PartyJackpotModeRamp);
             }
+            self.credit(ScoreSource::Jackpot, self.score_jackpot);
             self.score_main += self.score_jackpot;
             self.score_jackpot = self.assets.score_jackpot_init;
+            self.stats_jackpot();
             got_something = true;
             timeout = 410;
         }
@@ -691,7 +703,11 @@ impl Table {
             self.add_task(TaskKind::PartySideExtraBallFinish);
         } else {
             self.play_sfx_bind(SfxBind::RollTrigger);
-            self.score(Bcd::from_ascii(b"50030"), Bcd::ZERO);
+            self.score(
+                ScoreSource::Award("OUTER LANE"),
+                Bcd::from_ascii(b"50030"),
+                Bcd::ZERO,
+            );
         }
     }
 
@@ -742,7 +758,11 @@ impl Table {
             return;
         }
         self.light_set(LightBind::PartyPuke, which, true);
-        self.score_premult(Bcd::from_ascii(b"20070"), Bcd::from_ascii(b"1000"));
+        self.score_premult(
+            ScoreSource::Award("PUKE"),
+            Bcd::from_ascii(b"20070"),
+            Bcd::from_ascii(b"1000"),
+        );
         self.incr_jackpot();
         if self.light_all_lit(LightBind::PartyPuke) {
             self.light_blink(LightBind::PartyPuke, 0, 2, self.party.light_phase_puke);
@@ -802,6 +822,7 @@ impl Table {
             self.incr_jackpot();
             self.incr_jackpot();
             self.party.score_cyclone_skill_shot += Bcd::from_ascii(b"1000000");
+            self.credit(ScoreSource::SkillShot, self.party.score_cyclone_skill_shot);
             self.score_main += self.party.score_cyclone_skill_shot;
             self.effect(EffectBind::PartyCycloneSkillShot);
             self.silence_effect = true;
@@ -821,3 +842,147 @@ impl Table {
         self.silence_effect = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assets::table::{
+            lights::LightBind,
+            physics::{HitTrigger, RollTrigger},
+            script::{EffectBind, ScriptBind},
+            sound::JingleBind,
+        },
+        config::TableId,
+        table::{scenario::Scenario, tasks::TaskKind},
+    };
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn ducks() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.set_lights(LightBind::PartyDuckDrop, true);
+        let score = s.score();
+        s.hit(HitTrigger::PartyDuck(0));
+        assert_eq!(s.score(), score + 7510);
+        assert!(!s.lit(LightBind::PartyDuckDrop, 0));
+        assert!(s.task_queued(TaskKind::PartyDuckDrop(0)));
+        assert!(s.task_queued(TaskKind::PartyDuckUnblink(0)));
+        // A duck that is down can't be hit.
+        s.hit(HitTrigger::PartyDuck(0));
+        assert_eq!(s.score(), score + 7510);
+
+        let snack = s.table.party.cur_snack;
+        s.hit(HitTrigger::PartyDuck(1));
+        s.hit(HitTrigger::PartyDuck(2));
+        assert!(s.effect_fired(EffectBind::PartyDuckAll));
+        assert!(s.task_queued(TaskKind::PartyDuckAllUnblink));
+        assert!(s.table.party.snack_lit[snack as usize]);
+        assert_eq!(s.table.party.cur_snack, (snack + 1) % 3);
+        // The ducks come back up.
+        s.run(80);
+        assert!(!s.task_queued(TaskKind::PartyDuckAllUnblink));
+        for which in 0..3 {
+            assert!(s.lit(LightBind::PartyDuckDrop, which));
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn puke_lights_demon_rewards() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.set_lights(LightBind::PartyPuke, false);
+        s.set_lights(LightBind::PartyParty, false);
+        let score = s.score();
+        s.roll(RollTrigger::PartySkyridePuke(0));
+        assert_eq!(s.score(), score + 20070);
+        assert!(s.task_queued(TaskKind::PartyPukeUnblink(0)));
+        s.roll(RollTrigger::PartySkyridePuke(0));
+        assert_eq!(s.score(), score + 20070);
+
+        for which in 1..4 {
+            s.roll(RollTrigger::PartySkyridePuke(which));
+        }
+        assert!(s.task_queued(TaskKind::PartyPukeUnblinkAll));
+        assert!(s.table.party.demon_5m);
+        assert_eq!(s.table.party.demon_reward, 1);
+        assert!(s.lit(LightBind::PartyParty, 4));
+        assert!(s.effect_fired(EffectBind::PartyPartyY));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn happy_hour() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.set_lights(LightBind::PartyParty, true);
+        s.set_light(LightBind::PartyParty, 4, false);
+        s.set_lights(LightBind::PartyPuke, true);
+        s.set_light(LightBind::PartyPuke, 3, false);
+        s.roll(RollTrigger::PartySkyridePuke(3));
+        assert!(s.effect_fired(EffectBind::PartyHappyHour));
+        assert!(s.table.in_mode && s.table.in_mode_hit);
+        assert!(s.table.party.demon_jackpot_timed);
+        for which in 0..5 {
+            assert!(!s.lit(LightBind::PartyParty, which));
+        }
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn demon_jackpot() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.table.party.demon_jackpot = true;
+        let jackpot = s.table.score_jackpot.to_u64();
+        let score = s.score();
+        s.roll(RollTrigger::PartyDemon);
+        assert_eq!(s.score(), score + jackpot);
+        assert!(s.script_started(ScriptBind::PartyJackpot));
+        assert!(s.jingle_played(JingleBind::PartyJackpot));
+        assert!(s.task_queued(TaskKind::PartyDemonBlink(410)));
+        assert_eq!(s.table.score_jackpot, s.table.assets.score_jackpot_init);
+        assert!(!s.table.party.demon_jackpot);
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn skyride() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.set_lights(LightBind::PartyParty, false);
+        s.set_light(LightBind::PartyBonus, 3, false);
+        s.table.party.orbit_right_mb = false;
+        // The top of the skyride only counts when the ball came up the ramp.
+        s.roll(RollTrigger::PartySkyrideTop);
+        assert_eq!(s.table.party.skyride, 0);
+
+        s.roll(RollTrigger::PartySkyrideRamp);
+        s.roll(RollTrigger::PartySkyrideTop);
+        assert_eq!(s.table.party.skyride, 1);
+        assert!(s.lit(LightBind::PartySkyride, 0));
+        // Another skyride soon after lights the T.
+        s.roll(RollTrigger::PartySkyrideRamp);
+        s.roll(RollTrigger::PartySkyrideTop);
+        assert!(s.lit(LightBind::PartyParty, 3));
+        s.roll(RollTrigger::PartySkyrideRamp);
+        s.roll(RollTrigger::PartySkyrideTop);
+        assert_eq!(s.table.party.skyride, 0);
+        assert!(s.task_queued(TaskKind::PartySkyrideUnblink));
+        assert!(s.table.party.orbit_right_mb);
+        assert!(s.effect_fired(EffectBind::PartySkyrideLitMb));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn outer_lane() {
+        let mut s = Scenario::start(TableId::Table1);
+        s.set_light(LightBind::PartySideExtraBall, 0, false);
+        let score = s.score();
+        s.roll(RollTrigger::PartyLaneOuter);
+        assert_eq!(s.score(), score + 50030);
+
+        s.set_light(LightBind::PartySideExtraBall, 0, true);
+        s.ball_on(RollTrigger::PartyLaneOuter, (0, 0));
+        assert!(s.run_until(10, |s| s.table.extra_balls == 1));
+        assert!(!s.lit(LightBind::PartySideExtraBall, 0));
+        assert!(s.effect_fired(EffectBind::PartySideExtraBall));
+        assert!(s.task_queued(TaskKind::PartySideExtraBallFinish));
+    }
+}
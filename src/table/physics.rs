@@ -11,8 +11,9 @@ use crate::{
     bcd::Bcd,
 };
 
-use super::Table;
+use super::{breakdown::ScoreSource, events::GameEvent, feedback::Coil, Table};
 
+#[derive(Debug)]
 pub struct PushState {
     offset_f9: i16,
     speed: i16,
@@ -53,6 +54,7 @@ impl PushState {
     }
 }
 
+#[derive(Debug)]
 pub struct FlipperState {
     pub pos: i16,
     pub speed: i16,
@@ -77,6 +79,20 @@ impl FlipperState {
     }
 }
 
+/// How close to a flipper's reach the ball has to get, beyond its own radius, to count as
+/// coming at it.
+const FLIP_MARGIN: i16 = 4;
+
+/// Where the ball is headed, relative to a flipper.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BallApproach {
+    /// The ball is within the flipper's reach, or will be within the lookahead, and isn't on
+    /// its way up the table.
+    pub coming: bool,
+    /// The ball is within reach and barely moving, resting on the flipper.
+    pub resting: bool,
+}
+
 pub fn speed_fix(speed: i16, hifps: bool) -> i16 {
     if hifps {
         speed
@@ -129,6 +145,7 @@ impl Table {
             self.ball_move();
             self.flippers_physmap_update();
         }
+        self.trace_frame();
     }
 
     fn physics_check_collision(&mut self) -> Option<Collision> {
@@ -317,16 +334,34 @@ impl Table {
             let factor = if self.hifps { -166 } else { -138 };
             self.ball.speed = (
                 0,
-                factor * self.spring_pos as i16 - rand::thread_rng().gen_range(0..0x100),
+                factor * self.spring_pos as i16 - self.rng.gen_range(0..0x100),
             );
-            self.ball.rotation = rand::thread_rng().gen_range(0..0x10);
+            self.ball.rotation = self.rng.gen_range(0..0x10);
         }
+        let strength = (self.spring_pos as u16 * 0xff / 0x20) as u8;
+        self.fire_strength(Coil::Plunger, strength);
         let volume = self.spring_pos * 2;
         self.play_sfx_bind_volume(SfxBind::SpringUp, volume);
         self.spring_pos = 0;
     }
 
+    /// Follows an analog plunger.  A quick move forward releases the spring from where it was;
+    /// easing it back doesn't.
+    pub fn spring_analog(&mut self, pos: u8) {
+        let pos = (pos as u16 * 0x20 / 0xff) as u8;
+        if self.spring_released && self.spring_pos != 0 {
+            // Released, waiting for the next frame.
+            return;
+        }
+        self.spring_released = pos + 8 <= self.spring_pos;
+        if !self.spring_released {
+            self.spring_pos = pos;
+        }
+    }
+
     fn flippers_move(&mut self) {
+        // Tables with two flippers on a side still have one coil for it.
+        let mut fired: EnumMap<FlipperSide, bool> = EnumMap::default();
         for (fid, flipper) in &self.assets.flippers {
             let state = &mut self.flippers[fid];
             if self.flipper_state[flipper.side] && self.flippers_enabled {
@@ -334,6 +369,7 @@ impl Table {
             } else {
                 state.speed += state.accel_release;
             }
+            let prev_pos = state.pos;
             state.pos += state.speed;
             if state.pos < 55 {
                 state.pos = 0;
@@ -343,8 +379,16 @@ impl Table {
                 state.pos = flipper.pos_max;
                 state.speed = 0;
             }
+            if state.pos == flipper.pos_max && prev_pos < flipper.pos_max {
+                fired[flipper.side] = true;
+            }
             state.quantum = (state.pos / 55) as u16;
         }
+        for (side, fired) in fired {
+            if fired {
+                self.fire(Coil::Flipper(side));
+            }
+        }
     }
 
     pub fn flippers_physmap_update(&mut self) {
@@ -362,6 +406,36 @@ impl Table {
         }
     }
 
+    /// Checks the ball against every flipper's reach, looking `lookahead` frames ahead along
+    /// its current speed.
+    pub(super) fn ball_approach(&self, lookahead: i16) -> EnumMap<FlipperSide, BallApproach> {
+        let mut res: EnumMap<FlipperSide, BallApproach> = EnumMap::default();
+        if self.ball.frozen || self.ball.layer != Layer::Ground {
+            return res;
+        }
+        let (cx, cy) = self.ball.pos_center();
+        // Pixels per frame, in 1/16ths.
+        let substeps = self.physics_substeps() as i32;
+        let vx = ((self.ball.speed.0 as i32 * substeps) >> 6) as i16;
+        let vy = ((self.ball.speed.1 as i32 * substeps) >> 6) as i16;
+        for flipper in self.assets.flippers.values() {
+            let reach = 8 + FLIP_MARGIN;
+            let bbox = flipper.ball_bbox;
+            let near = |(x, y): (i16, i16)| {
+                (bbox.xy_min.0 - reach..=bbox.xy_max.0 + reach).contains(&x)
+                    && (bbox.xy_min.1 - reach..=bbox.xy_max.1 + reach).contains(&y)
+            };
+            let here = near((cx, cy));
+            let coming =
+                (0..=lookahead).any(|t| near((cx + ((vx * t) >> 4), cy + ((vy * t) >> 4))));
+            let approach = &mut res[flipper.side];
+            // Moving away up the table, there's nothing to hit.
+            approach.coming |= coming && (vy > -16 || here);
+            approach.resting |= here && vx.abs() < 24 && vy.abs() < 24;
+        }
+        res
+    }
+
     pub fn drop_physmap(&mut self, bind: PhysmapBind) {
         let patch = self.assets.physmap_patches[bind].as_ref().unwrap();
         physmap_patch(&mut self.physmaps, patch.layer, patch.pos, &patch.dropped);
@@ -420,8 +494,10 @@ impl Table {
         if let Some(bid) = self.hit_bumper {
             self.hit_bumper = None;
             let bumper = &self.assets.bumpers[bid];
-            self.player.play_sfx(bumper.sfx, 0x40);
-            self.score(bumper.score, Bcd::ZERO);
+            self.play_sfx(bumper.sfx, 0x40);
+            self.emit(GameEvent::Bumper { bumper: bid });
+            self.score(ScoreSource::Bumper, bumper.score, Bcd::ZERO);
+            self.fire(Coil::Bumper(bid));
             self.mode_count_hit();
         }
     }
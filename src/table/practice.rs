@@ -0,0 +1,287 @@
+//! Practice mode.  Dragging with the left mouse button on the board throws the ball from
+//! where the drag started, in the direction and with the speed of the drag.  The last shot
+//! can be replayed, saved to one of nine numbered slots, and replayed automatically whenever
+//! the ball drains.  Scores never reach the high score table.
+
+use arrayvec::ArrayVec;
+use winit::{
+    event::{ElementState, MouseButton},
+    keyboard::KeyCode,
+};
+
+use crate::{
+    assets::table::{dm::DmFont, physics::Layer, script::DmCoord},
+    config::{PracticeShot, PRACTICE_SLOTS},
+    view::Action,
+};
+
+use super::{KbdState, Table};
+
+// Drag distance to ball speed.  Dragging across 128 pixels gives the maximum speed.
+const DRAG_SPEED: i16 = 32;
+
+#[derive(Debug)]
+pub struct PracticeState {
+    shots: [Option<PracticeShot>; PRACTICE_SLOTS],
+    /// The last shot, and the slot it was replayed from.
+    cur: Option<PracticeShot>,
+    slot: Option<usize>,
+    /// The layer new shots are placed on.
+    layer: Layer,
+    auto_replay: bool,
+    drag_start: Option<(i16, i16)>,
+    name_buf: ArrayVec<u8, 8>,
+    changed: bool,
+}
+
+impl Table {
+    pub fn set_practice(&mut self, shots: [Option<PracticeShot>; PRACTICE_SLOTS]) {
+        self.practice = Some(PracticeState {
+            shots,
+            cur: None,
+            slot: None,
+            layer: Layer::Ground,
+            auto_replay: false,
+            drag_start: None,
+            name_buf: ArrayVec::new(),
+            changed: false,
+        });
+    }
+
+    pub(super) fn in_practice(&self) -> bool {
+        self.practice.is_some()
+    }
+
+    fn practice_can_shoot(&self) -> bool {
+        !self.in_attract
+            && !self.quitting
+            && !self.in_drain
+            && self.flippers_enabled
+            && self.kbd_state == KbdState::Main
+    }
+
+    fn practice_msg(&mut self, msg: &[u8]) {
+        self.dm.clear();
+        let x = (160 - 8 * msg.len().min(20) as i16) / 2;
+        self.dm_puts(DmFont::H13, DmCoord { x, y: 1 }, msg);
+    }
+
+    fn practice_shoot(&mut self, shot: PracticeShot) {
+        self.ball.teleport(shot.layer, shot.pos, shot.speed);
+        self.drained = false;
+        if self.in_plunger {
+            self.enter();
+        }
+        self.practice.as_mut().unwrap().cur = Some(shot);
+    }
+
+    pub(super) fn handle_practice_mouse(
+        &mut self,
+        pos: (i32, i32),
+        button: MouseButton,
+        state: ElementState,
+    ) -> bool {
+        if self.practice.is_none() || button != MouseButton::Left || !self.practice_can_shoot() {
+            return false;
+        }
+        if !(0..320).contains(&pos.0) || !(0..self.board_height() as i32).contains(&pos.1) {
+            return false;
+        }
+        let pos = (pos.0 as i16, pos.1 as i16 + self.scroll.pos() as i16);
+        let max_speed = self.ball.max_speed;
+        let practice = self.practice.as_mut().unwrap();
+        match state {
+            ElementState::Pressed => practice.drag_start = Some(pos),
+            ElementState::Released => {
+                let Some(start) = practice.drag_start.take() else {
+                    return false;
+                };
+                let speed = |from: i16, to: i16| {
+                    (to - from)
+                        .saturating_mul(DRAG_SPEED)
+                        .clamp(-max_speed, max_speed)
+                };
+                let shot = PracticeShot {
+                    name: [b' '; 8],
+                    layer: practice.layer,
+                    pos: (
+                        (start.0 - 8).clamp(0, 320 - 16),
+                        (start.1 - 8).clamp(0, 576 - 16),
+                    ),
+                    speed: (speed(start.0, pos.0), speed(start.1, pos.1)),
+                };
+                practice.slot = None;
+                self.practice_shoot(shot);
+            }
+        }
+        true
+    }
+
+    /// Handles the practice mode keys during a game.
+    pub(super) fn handle_practice_key(&mut self, key: KeyCode) -> bool {
+        let Some(practice) = &mut self.practice else {
+            return false;
+        };
+        let slot = match key {
+            KeyCode::Digit1 => 0,
+            KeyCode::Digit2 => 1,
+            KeyCode::Digit3 => 2,
+            KeyCode::Digit4 => 3,
+            KeyCode::Digit5 => 4,
+            KeyCode::Digit6 => 5,
+            KeyCode::Digit7 => 6,
+            KeyCode::Digit8 => 7,
+            KeyCode::Digit9 => 8,
+            KeyCode::KeyL => {
+                practice.layer = match practice.layer {
+                    Layer::Ground => Layer::Overhead,
+                    Layer::Overhead => Layer::Ground,
+                };
+                let msg: &[u8] = match practice.layer {
+                    Layer::Ground => b"GROUND LAYER",
+                    Layer::Overhead => b"OVERHEAD LAYER",
+                };
+                self.practice_msg(msg);
+                return true;
+            }
+            KeyCode::KeyA => {
+                practice.auto_replay = !practice.auto_replay;
+                let msg: &[u8] = if practice.auto_replay {
+                    b"AUTO REPLAY ON"
+                } else {
+                    b"AUTO REPLAY OFF"
+                };
+                self.practice_msg(msg);
+                return true;
+            }
+            KeyCode::KeyR => {
+                let cur = practice.cur;
+                match cur {
+                    Some(shot) if self.practice_can_shoot() => self.practice_shoot(shot),
+                    Some(_) => (),
+                    None => self.practice_msg(b"NO SHOT YET"),
+                }
+                return true;
+            }
+            KeyCode::KeyS => {
+                if practice.cur.is_none() {
+                    self.practice_msg(b"NO SHOT YET");
+                    return true;
+                }
+                practice.name_buf.clear();
+                if let Some(slot) = practice.slot {
+                    let name = practice.shots[slot].unwrap().name;
+                    practice
+                        .name_buf
+                        .extend(name.into_iter().filter(|&c| c != b' '));
+                }
+                self.dm.save();
+                self.player.pause();
+                self.kbd_state = KbdState::PracticeName;
+                self.practice_draw_name();
+                return true;
+            }
+            _ => return false,
+        };
+        match practice.shots[slot] {
+            Some(shot) => {
+                practice.slot = Some(slot);
+                if self.practice_can_shoot() {
+                    self.practice_shoot(shot);
+                }
+            }
+            None => self.practice_msg(format!("SLOT {n} EMPTY", n = slot + 1).as_bytes()),
+        }
+        true
+    }
+
+    fn practice_draw_name(&mut self) {
+        let mut msg = b"NAME ".to_vec();
+        msg.extend(&self.practice.as_ref().unwrap().name_buf);
+        self.dm.clear();
+        self.dm_puts(DmFont::H13, DmCoord { x: 0, y: 1 }, &msg);
+    }
+
+    /// Handles keys while naming the shot being saved.
+    pub(super) fn handle_practice_name_key(&mut self, key: KeyCode, chr: Option<u8>) {
+        let practice = self.practice.as_mut().unwrap();
+        let digit = match key {
+            KeyCode::Digit0 => Some(b'0'),
+            KeyCode::Digit1 => Some(b'1'),
+            KeyCode::Digit2 => Some(b'2'),
+            KeyCode::Digit3 => Some(b'3'),
+            KeyCode::Digit4 => Some(b'4'),
+            KeyCode::Digit5 => Some(b'5'),
+            KeyCode::Digit6 => Some(b'6'),
+            KeyCode::Digit7 => Some(b'7'),
+            KeyCode::Digit8 => Some(b'8'),
+            KeyCode::Digit9 => Some(b'9'),
+            _ => None,
+        };
+        match key {
+            KeyCode::Enter | KeyCode::Escape => (),
+            KeyCode::Backspace => {
+                practice.name_buf.pop();
+                self.practice_draw_name();
+                return;
+            }
+            _ => {
+                if let Some(chr) = chr.or(digit) {
+                    let _ = practice.name_buf.try_push(chr);
+                    self.practice_draw_name();
+                }
+                return;
+            }
+        }
+        self.dm.restore();
+        self.player.unpause();
+        self.kbd_state = KbdState::Main;
+        // Space is also the tilt key.
+        self.space_pressed = false;
+        if key == KeyCode::Escape {
+            return;
+        }
+        let Some(slot) = practice
+            .slot
+            .or_else(|| practice.shots.iter().position(|shot| shot.is_none()))
+        else {
+            self.practice_msg(b"NO FREE SLOT");
+            return;
+        };
+        let mut shot = practice.cur.unwrap();
+        let mut name = practice.name_buf.clone();
+        if name.is_empty() {
+            name.extend(format!("SHOT {n}", n = slot + 1).bytes());
+        }
+        shot.name = [b' '; 8];
+        shot.name[..name.len()].copy_from_slice(&name);
+        practice.shots[slot] = Some(shot);
+        practice.cur = Some(shot);
+        practice.slot = Some(slot);
+        practice.changed = true;
+        self.practice_msg(format!("SAVED TO SLOT {n}", n = slot + 1).as_bytes());
+    }
+
+    /// Called when the ball drains.  Returns `true` if the ball was thrown again instead.
+    pub(super) fn practice_drained(&mut self) -> bool {
+        let Some(practice) = &self.practice else {
+            return false;
+        };
+        match practice.cur {
+            Some(shot) if practice.auto_replay && !self.tilted => {
+                self.practice_shoot(shot);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(super) fn practice_flush(&mut self) -> Option<Action> {
+        let practice = self.practice.as_mut()?;
+        if !practice.changed {
+            return None;
+        }
+        practice.changed = false;
+        Some(Action::SavePracticeShots(self.assets.table, practice.shots))
+    }
+}
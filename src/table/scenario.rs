@@ -0,0 +1,226 @@
+//! Scripted scenarios for testing table rules.  A scenario starts a one-player game on a
+//! headless table, sets up the lights, flags and ball a rule needs, sets it off, and checks
+//! what happened.
+//!
+//! The table data isn't part of the repository, so the tests using it are ignored by default.
+//! They read it from the directory given in `PFR_DATA`:
+//!
+//! ```text
+//! PFR_DATA=<game directory> cargo test -- --ignored
+//! ```
+
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    rc::Rc,
+    sync::{Arc, OnceLock},
+};
+
+use winit::{event::ElementState, keyboard::KeyCode};
+
+use crate::{
+    assets::table::{
+        lights::LightBind,
+        physics::{HitTrigger, Layer, RollTrigger},
+        script::{EffectBind, ScriptBind},
+        sound::JingleBind,
+        Assets,
+    },
+    cache::{AssetCache, TableData},
+    config::{Config, TableId},
+    view::View,
+};
+
+use super::{
+    breakdown::ScoreSource,
+    events::{EventSink, GameEvent},
+    tasks::TaskKind,
+    Table,
+};
+
+// A game that hasn't started after this long never will.
+const START_FRAMES: u32 = 60 * 10;
+
+/// The directory with the original game data, from `PFR_DATA`.
+pub fn data_dir() -> PathBuf {
+    std::env::var_os("PFR_DATA")
+        .unwrap_or_else(|| {
+            panic!("PFR_DATA isn't set; run `PFR_DATA=<game directory> cargo test -- --ignored`")
+        })
+        .into()
+}
+
+/// Loads a table from [`data_dir`], once for all the tests.
+pub fn table_data(table: TableId) -> TableData {
+    static CACHE: OnceLock<AssetCache> = OnceLock::new();
+    let dir = data_dir();
+    CACHE.get_or_init(AssetCache::new).get_or_load(table, || {
+        let (prg, module) = match table {
+            TableId::Table1 => ("TABLE1.PRG", "TABLE1.MOD"),
+            TableId::Table2 => ("TABLE2.PRG", "TABLE2.MOD"),
+            TableId::Table3 => ("TABLE3.PRG", "TABLE3.MOD"),
+            TableId::Table4 => ("TABLE4.PRG", "TABLE4.MOD"),
+        };
+        let read =
+            |name: &str| std::fs::read(dir.join(name)).unwrap_or_else(|e| panic!("{name}: {e}"));
+        TableData {
+            assets: Arc::new(Assets::load(&read(prg), table)),
+            module: Arc::new(crate::sound::loader::load(&read(module))),
+        }
+    })
+}
+
+#[derive(Default)]
+struct EventLog(Vec<GameEvent>);
+
+impl EventSink for EventLog {
+    fn event(&mut self, _frame: u64, event: &GameEvent) {
+        self.0.push(event.clone());
+    }
+}
+
+pub struct Scenario {
+    pub table: Table,
+    events: Rc<RefCell<EventLog>>,
+}
+
+impl Scenario {
+    /// Starts a game, with the ball in play but still resting on the plunger.  Every scenario
+    /// on the same table gets the same random numbers.
+    pub fn start(table: TableId) -> Self {
+        let data = table_data(table);
+        let mut view = Table::from_assets_silent(data.assets, data.module, Config::default());
+        view.seed(0);
+        view.set_keep_records(false);
+        view.handle_key(KeyCode::F1, ElementState::Pressed);
+        view.handle_key(KeyCode::F1, ElementState::Released);
+        for _ in 0..START_FRAMES {
+            view.run_frame();
+            if !view.in_attract {
+                break;
+            }
+        }
+        assert!(!view.in_attract, "game didn't start");
+        view.enter();
+        view.sequencer.reset_priority();
+        let events = Rc::new(RefCell::new(EventLog::default()));
+        view.set_event_sink(Box::new(events.clone()));
+        Self {
+            table: view,
+            events,
+        }
+    }
+
+    pub fn run(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.table.run_frame();
+        }
+    }
+
+    /// Runs until `cond` holds, for at most `frames` frames.  Returns whether it did.
+    pub fn run_until(&mut self, frames: u32, mut cond: impl FnMut(&Self) -> bool) -> bool {
+        for _ in 0..frames {
+            if cond(self) {
+                return true;
+            }
+            self.table.run_frame();
+        }
+        cond(self)
+    }
+
+    /// Puts the ball's center at `pos`, moving at `speed`.
+    pub fn ball(&mut self, layer: Layer, pos: (i16, i16), speed: (i16, i16)) {
+        self.table
+            .ball
+            .teleport(layer, (pos.0 - 8, pos.1 - 8), speed);
+        self.table.drained = false;
+    }
+
+    /// Puts the ball in the middle of a roll trigger, moving at `speed`.
+    pub fn ball_on(&mut self, kind: RollTrigger, speed: (i16, i16)) {
+        let (layer, rect) = self
+            .table
+            .assets
+            .roll_triggers
+            .iter()
+            .find_map(|(layer, areas)| {
+                areas
+                    .iter()
+                    .find(|area| area.kind == kind)
+                    .map(|area| (layer, area.rect))
+            })
+            .unwrap_or_else(|| panic!("no roll trigger {kind:?}"));
+        self.ball(
+            layer,
+            (
+                (rect.xy_min.0 + rect.xy_max.0) / 2,
+                (rect.xy_min.1 + rect.xy_max.1) / 2,
+            ),
+            speed,
+        );
+    }
+
+    /// The ball rolls over a trigger and off it again.  The trigger is remembered for the
+    /// rules that look at where the ball came from, just like in a real game.
+    pub fn roll(&mut self, kind: RollTrigger) {
+        self.table.roll_trigger = Some(kind);
+        self.table.do_roll_trigger(kind);
+        self.table.prev_roll_trigger = self.table.roll_trigger;
+        self.table.roll_trigger = None;
+    }
+
+    pub fn hit(&mut self, kind: HitTrigger) {
+        self.table.do_hit_trigger(kind);
+    }
+
+    /// The current player's main score.
+    pub fn score(&self) -> u64 {
+        self.table.score_main.to_u64()
+    }
+
+    pub fn lit(&self, bind: LightBind, idx: u8) -> bool {
+        self.table.light_state(bind, idx)
+    }
+
+    pub fn set_light(&mut self, bind: LightBind, idx: u8, state: bool) {
+        self.table.light_set(bind, idx, state);
+    }
+
+    pub fn set_lights(&mut self, bind: LightBind, state: bool) {
+        self.table.light_set_all(bind, state);
+    }
+
+    pub fn task_queued(&self, kind: TaskKind) -> bool {
+        self.table.tasks.iter().any(|task| task.kind() == kind)
+    }
+
+    /// Whether the table code started the script since the last [`Scenario::clear_events`].
+    pub fn script_started(&self, bind: ScriptBind) -> bool {
+        self.events
+            .borrow()
+            .0
+            .iter()
+            .any(|event| matches!(event, GameEvent::Script { bind: b } if *b == bind))
+    }
+
+    /// Whether the jingle got to play, rather than losing to one with a higher priority.
+    pub fn jingle_played(&self, bind: JingleBind) -> bool {
+        let jingle = self.table.assets.jingle_binds[bind].unwrap();
+        self.events.borrow().0.iter().any(|event| {
+            matches!(event, GameEvent::Jingle { position, played: true, .. }
+                if *position == jingle.position)
+        })
+    }
+
+    /// Whether the effect was set off, whether or not its jingle or script got to play.
+    pub fn effect_fired(&self, bind: EffectBind) -> bool {
+        self.events.borrow().0.iter().any(|event| {
+            matches!(event, GameEvent::Effect { source: ScoreSource::Effect(b), .. }
+                if *b == bind)
+        })
+    }
+
+    pub fn clear_events(&mut self) {
+        self.events.borrow_mut().0.clear();
+    }
+}
@@ -1,6 +1,7 @@
 use arrayref::array_ref;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use unnamed_entity::EntityId;
+use web_time::{SystemTime, UNIX_EPOCH};
 
 use crate::{
     assets::table::{
@@ -10,7 +11,7 @@ use crate::{
         sound::JingleBind,
     },
     bcd::Bcd,
-    config::{HighScore, TableId},
+    config::{HighScore, HighScoreDetails, TableId},
 };
 
 use super::{
@@ -18,11 +19,13 @@ use super::{
         ScriptTaskDmAnim, ScriptTaskDmLongMsg, ScriptTaskDmMsgScroll, ScriptTaskDmTowerHunt,
         ScriptTaskDmWipeDown, ScriptTaskDmWipeDownStriped, ScriptTaskDmWipeRight,
     },
+    events::GameEvent,
     game::{ScriptTaskAccBonus, ScriptTaskMatch, ScriptTaskMatchStones},
     tasks::TaskKind,
     KbdState, Table,
 };
 
+#[derive(Debug)]
 pub struct ScriptState {
     pos: ScriptPosId,
     task: ScriptTask,
@@ -133,10 +136,13 @@ impl ScriptTask {
                         table.play_jingle_bind_force(JingleBind::GameOverSad);
                     }
                     false
+                } else if !table.game_on_record() {
+                    table.cur_player += 1;
+                    true
                 } else {
                     let score = table.players[table.cur_player as usize - 1].score_main;
-                    for place in 0..4 {
-                        if score > table.high_scores[place].score {
+                    match table.high_scores.place(score) {
+                        Some(place) if place < 4 => {
                             if !table.got_high_score {
                                 table.play_jingle_bind_force(JingleBind::GameOverHighScore);
                                 table.got_high_score = true;
@@ -151,6 +157,13 @@ impl ScriptTask {
                             table.name_buf.clear();
                             return true;
                         }
+                        Some(place) => {
+                            // Only the DOS top four ask for a name; the rest of the list is
+                            // filled in quietly under the player number.
+                            let name = [b'P', b'L', b'0' + table.cur_player];
+                            table.record_high_score(place, name);
+                        }
+                        None => (),
                     }
                     table.cur_player += 1;
                     true
@@ -167,14 +180,8 @@ impl ScriptTask {
                     &name,
                 );
                 if name.len() == 3 {
-                    let score = HighScore {
-                        score: table.players[table.cur_player as usize - 1].score_main,
-                        name: *array_ref![name, 0, 3],
-                    };
-                    table.high_scores.copy_within(place..3, place + 1);
-                    table.high_scores[place] = score;
+                    table.record_high_score(place, *array_ref![name, 0, 3]);
                     table.cur_player += 1;
-                    table.flush_high_scores = true;
                     *self = ScriptTask::RecordHighScoresFinish(60);
                 }
                 true
@@ -205,6 +212,10 @@ impl ScriptState {
             repeat_cnt: 0,
         }
     }
+
+    pub fn pos(&self) -> ScriptPosId {
+        self.pos
+    }
 }
 
 impl Table {
@@ -219,7 +230,26 @@ impl Table {
         }
     }
 
+    fn record_high_score(&mut self, place: usize, name: [u8; 3]) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let score = HighScore {
+            score: self.players[self.cur_player as usize - 1].score_main,
+            name,
+            details: Some(HighScoreDetails {
+                time,
+                balls: self.options.balls,
+                angle_high: self.options.angle_high,
+                players: self.total_players,
+            }),
+        };
+        self.high_scores.insert(place, score);
+        self.flush_high_scores = true;
+    }
+
     pub fn start_script(&mut self, bind: ScriptBind) {
+        self.emit(GameEvent::Script { bind });
         self.start_script_raw(self.assets.script_binds[bind].unwrap());
     }
 
@@ -358,10 +388,12 @@ impl Table {
             Uop::AccBonusModeHit => {
                 self.script.task = ScriptTask::Delay(1);
                 self.score_bonus += self.score_mode_hit;
+                self.credit_mode_bonus(self.score_mode_hit);
             }
             Uop::AccBonusModeRamp => {
                 self.script.task = ScriptTask::Delay(1);
                 self.score_bonus += self.score_mode_ramp;
+                self.credit_mode_bonus(self.score_mode_ramp);
             }
             Uop::AccBonusCyclones => {
                 self.script.task = ScriptTask::Delay(1);
@@ -399,6 +431,7 @@ impl Table {
                 }
             }
             Uop::NextBall => {
+                self.breakdown_next_ball();
                 if !self.hold_bonus {
                     self.score_bonus = Bcd::ZERO;
                 }
@@ -434,7 +467,7 @@ impl Table {
                         &[b'0' + digit],
                     );
                 }
-                let digit = thread_rng().gen_range(0..10);
+                let digit = self.rng.gen_range(0..10);
                 self.script.task = match self.assets.table {
                     TableId::Table1 => ScriptTask::Match(ScriptTaskMatch {
                         count: 22,
@@ -466,6 +499,7 @@ impl Table {
                 for (i, player) in self.players.iter().enumerate() {
                     if self.match_digit == Some(player.score_main.digits[10]) {
                         self.cur_player = i as u8 + 1;
+                        self.stat(|stats| stats.match_wins += 1);
                         self.run_uop(self.assets.script_binds[ScriptBind::ShootAgain].unwrap());
                         found = true;
                         break;
@@ -509,7 +543,7 @@ impl Table {
                     ScriptTask::DmWipeDownStriped(ScriptTaskDmWipeDownStriped::new());
             }
             Uop::DmAnim(anim) => {
-                self.script.task = ScriptTask::DmAnim(ScriptTaskDmAnim::new(self, anim));
+                self.script.task = ScriptTask::DmAnim(ScriptTaskDmAnim::new(&self.assets, anim));
             }
             Uop::DmPuts(font, pos, msg) => {
                 self.script.task = ScriptTask::Delay(1);
@@ -542,11 +576,11 @@ impl Table {
             Uop::WaitJingle | Uop::WaitJingleTimeout => self.script.task = ScriptTask::WaitJingle,
             Uop::PlayJingle(jingle) => {
                 self.script.task = ScriptTask::Delay(1);
-                self.sequencer.play_jingle(jingle, true, None);
+                self.play_jingle(jingle, true, None);
             }
             Uop::PlaySfx(sfx, volume) => {
                 self.script.task = ScriptTask::Delay(1);
-                self.player.play_sfx(sfx, volume);
+                self.play_sfx(sfx, volume);
             }
 
             Uop::SetMusic(position) => {
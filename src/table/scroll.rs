@@ -1,5 +1,6 @@
 use crate::config::{Options, Resolution};
 
+#[derive(Debug)]
 pub struct ScrollState {
     pos: u16,
     raw_pos_f4: i16,
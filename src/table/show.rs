@@ -1,4 +1,4 @@
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
 use crate::{
     assets::table::{
@@ -11,7 +11,12 @@ use crate::{
     config::Resolution,
 };
 
-use super::{tasks::TaskKind, Table};
+use super::{
+    breakdown::ScoreSource,
+    feedback::{Coil, Kicker},
+    tasks::TaskKind,
+    Table,
+};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub enum PrizeState {
@@ -20,6 +25,7 @@ pub enum PrizeState {
     Taken,
 }
 
+#[derive(Debug)]
 pub struct ShowState {
     pub score_cashpot: Bcd,
     pub prizes: [PrizeState; 6],
@@ -247,7 +253,7 @@ impl Table {
             self.start_script(ScriptBind::ShowSpinWheelClearHalt);
             self.light_set_all(LightBind::ShowWheel, false);
             let target: u8 = if !self.light_state(LightBind::ShowCollectPrize, 0) {
-                thread_rng().gen_range(0..8)
+                self.rng.gen_range(0..8)
             } else if self.show.prizes[0] == PrizeState::Lit {
                 0
             } else if self.show.prizes[1] == PrizeState::Lit {
@@ -352,6 +358,7 @@ impl Table {
             self.sequencer.set_music(0);
             self.sequencer.reset_priority();
             self.ball.teleport(Layer::Ground, (304, 535), (10, 0));
+            self.fire(Coil::Trough);
             self.drop_physmap(PhysmapBind::ShowGateVaultEntry);
         } else {
             self.incr_jackpot();
@@ -361,11 +368,11 @@ impl Table {
                 for _ in 0..5 {
                     effect.score_main += self.show.score_cashpot;
                 }
-                self.effect_raw(effect);
+                self.effect_raw(ScoreSource::Effect(EffectBind::ShowCashpotX5), effect);
             } else {
                 let mut effect = self.assets.effects[EffectBind::ShowCashpot].unwrap();
                 effect.score_main = self.show.score_cashpot;
-                self.effect_raw(effect);
+                self.effect_raw(ScoreSource::Effect(EffectBind::ShowCashpot), effect);
             }
             self.ball.teleport_freeze(Layer::Ground, (103, 233));
             self.add_task(TaskKind::ShowCashpot);
@@ -376,6 +383,7 @@ impl Table {
         self.play_sfx_bind(SfxBind::ShowEjectCashpot);
         self.light_set(LightBind::ShowCashpot, 0, false);
         self.ball.teleport(Layer::Ground, (103, 233), (83, 1416));
+        self.fire(Coil::Kicker(Kicker::ShowCashpot));
     }
 
     pub fn show_ramp_right(&mut self) {
@@ -389,8 +397,9 @@ impl Table {
             self.show.timeout_jackpot = 1;
             let mut effect = self.assets.effects[EffectBind::ShowJackpot].unwrap();
             effect.score_main = self.score_jackpot;
-            self.effect_raw(effect);
+            self.effect_raw(ScoreSource::Jackpot, effect);
             self.score_jackpot = self.assets.score_jackpot_init;
+            self.stats_jackpot();
             self.show.timeout_super_jackpot = 300;
             self.light_blink(LightBind::ShowSuperJackpot, 0, 10, 0);
             self.play_jingle_bind(JingleBind::ShowJackpot);
@@ -466,7 +475,7 @@ impl Table {
                 self.score_raising_millions += Bcd::from_ascii(b"1000000");
                 let mut effect = self.assets.effects[EffectBind::ShowRaisingMillions].unwrap();
                 effect.score_main = self.score_raising_millions;
-                self.effect_raw(effect);
+                self.effect_raw(ScoreSource::Effect(EffectBind::ShowRaisingMillions), effect);
             }
         }
         if self.show.timeout_mb != 0 {
@@ -608,3 +617,86 @@ impl Table {
         self.light_blink(LightBind::ShowTopLoop, 0, 10, self.show.light_phase_prize);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assets::table::{
+            lights::LightBind,
+            physics::{HitTrigger, RollTrigger},
+            script::{EffectBind, ScriptBind},
+            sound::JingleBind,
+        },
+        bcd::Bcd,
+        config::TableId,
+        table::{scenario::Scenario, tasks::TaskKind},
+    };
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn dollar_targets() {
+        let mut s = Scenario::start(TableId::Table3);
+        s.set_lights(LightBind::ShowDollar, false);
+        s.set_light(LightBind::ShowSpinWheel, 0, false);
+        s.hit(HitTrigger::ShowDollar(0));
+        assert!(s.effect_fired(EffectBind::ShowDollar));
+        assert!(s.task_queued(TaskKind::ShowUnblinkDollar(0)));
+        assert!(!s.lit(LightBind::ShowSpinWheel, 0));
+        s.hit(HitTrigger::ShowDollar(1));
+        assert!(s.effect_fired(EffectBind::ShowDollarBoth));
+        assert!(s.task_queued(TaskKind::ShowUnblinkDollarAll));
+        assert!(s.lit(LightBind::ShowSpinWheel, 0));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn drop_targets() {
+        let mut s = Scenario::start(TableId::Table3);
+        s.set_lights(LightBind::ShowDropCenter, true);
+        s.hit(HitTrigger::ShowCenter(0));
+        assert!(s.effect_fired(EffectBind::ShowDropCenter));
+        assert!(!s.lit(LightBind::ShowDropCenter, 0));
+        // A target that is down can't be hit.
+        s.clear_events();
+        s.hit(HitTrigger::ShowCenter(0));
+        assert!(!s.effect_fired(EffectBind::ShowDropCenter));
+
+        s.hit(HitTrigger::ShowCenter(1));
+        assert!(s.task_queued(TaskKind::ShowResetDropCenter));
+        assert!(s.run_until(120, |s| s.lit(LightBind::ShowDropCenter, 0)
+            && s.lit(LightBind::ShowDropCenter, 1)));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn right_ramp_jackpot() {
+        let mut s = Scenario::start(TableId::Table3);
+        s.table.show.timeout_jackpot = 100;
+        let jackpot = s.table.score_jackpot.to_u64();
+        let cashpot = s.table.show.score_cashpot;
+        let score = s.score();
+        s.roll(RollTrigger::ShowRampRight);
+        assert!(s.effect_fired(EffectBind::ShowRampRight));
+        assert!(s.score() >= score + jackpot);
+        assert_eq!(s.table.score_jackpot, s.table.assets.score_jackpot_init);
+        assert_eq!(s.table.show.timeout_super_jackpot, 300);
+        assert_eq!(
+            s.table.show.score_cashpot,
+            cashpot + Bcd::from_ascii(b"7130")
+        );
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn vault_spins_wheel() {
+        let mut s = Scenario::start(TableId::Table3);
+        s.set_light(LightBind::ShowCollectPrize, 0, false);
+        s.table.show.billion_lit = false;
+        s.roll(RollTrigger::ShowVault);
+        assert!(s.table.ball.frozen);
+        assert!(s.script_started(ScriptBind::ShowSpinWheelClearHalt));
+        assert!(s.jingle_played(JingleBind::ShowSpinWheel));
+        assert!(s.run_until(60 * 20, |s| s.task_queued(TaskKind::ShowSpinWheelEnd)));
+        assert!(s.script_started(ScriptBind::ShowSpinWheelBlink));
+    }
+}
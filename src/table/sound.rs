@@ -9,25 +9,24 @@ impl Table {
 
     pub fn play_sfx_bind_volume(&self, bind: SfxBind, volume: u8) {
         if let Some(sfx) = self.assets.sfx_binds[bind] {
-            self.player.play_sfx(sfx, volume);
+            self.play_sfx(sfx, volume);
         }
     }
 
     pub fn play_jingle_bind(&self, bind: JingleBind) -> bool {
         let jingle = self.assets.jingle_binds[bind].unwrap();
-        self.sequencer.play_jingle(jingle, false, None)
+        self.play_jingle(jingle, false, None)
     }
 
     pub fn play_jingle_bind_force(&self, bind: JingleBind) -> bool {
         let jingle = self.assets.jingle_binds[bind].unwrap();
-        self.sequencer.play_jingle(jingle, true, None)
+        self.play_jingle(jingle, true, None)
     }
 
     pub fn play_jingle_bind_silence(&self, bind: JingleBind) -> bool {
         let jingle = self.assets.jingle_binds[bind].unwrap();
         let silence = self.assets.jingle_binds[JingleBind::Silence].unwrap();
-        self.sequencer
-            .play_jingle(jingle, false, Some(silence.position))
+        self.play_jingle(jingle, false, Some(silence.position))
     }
 
     pub fn set_music_silence(&self) {
@@ -65,7 +64,6 @@ impl Table {
             JingleBind::Plunger
         }]
         .unwrap();
-        self.sequencer
-            .play_jingle(jingle, false, Some(jingle.position));
+        self.play_jingle(jingle, false, Some(jingle.position));
     }
 }
@@ -8,8 +8,9 @@ use crate::{
     bcd::Bcd,
 };
 
-use super::{tasks::TaskKind, Table};
+use super::{breakdown::ScoreSource, tasks::TaskKind, Table};
 
+#[derive(Debug)]
 pub struct SpeedState {
     pub blink_bur: [bool; 3],
     pub blink_nin: [bool; 3],
@@ -144,7 +145,11 @@ impl Table {
         self.speed.blink_bur[which as usize] = true;
         self.light_set(LightBind::SpeedBur, which, true);
         self.mode_count_hit();
-        self.score_premult(Bcd::from_ascii(b"7510"), Bcd::from_ascii(b"550"));
+        self.score_premult(
+            ScoreSource::Award("BURNIN"),
+            Bcd::from_ascii(b"7510"),
+            Bcd::from_ascii(b"550"),
+        );
         self.play_sfx_bind(SfxBind::SpeedHitTarget);
         if self.light_all_lit(LightBind::SpeedBur) {
             self.incr_jackpot();
@@ -167,7 +172,11 @@ impl Table {
         self.speed.blink_nin[which as usize] = true;
         self.light_set(LightBind::SpeedNin, which, true);
         self.mode_count_hit();
-        self.score_premult(Bcd::from_ascii(b"7510"), Bcd::from_ascii(b"550"));
+        self.score_premult(
+            ScoreSource::Award("BURNIN"),
+            Bcd::from_ascii(b"7510"),
+            Bcd::from_ascii(b"550"),
+        );
         self.play_sfx_bind(SfxBind::SpeedHitTarget);
         if self.light_all_lit(LightBind::SpeedNin) {
             self.incr_jackpot();
@@ -357,8 +366,10 @@ impl Table {
         self.mode_count_ramp();
         self.incr_jackpot();
         if self.light_state(LightBind::SpeedMiniRampJackpot, 0) {
+            self.credit(ScoreSource::Jackpot, self.score_jackpot);
             self.score_main += self.score_jackpot;
             self.score_jackpot = self.assets.score_jackpot_init;
+            self.stats_jackpot();
             if self.in_mode_ramp {
                 self.effect_force(EffectBind::SpeedJackpot);
                 self.timer_stop = true;
@@ -586,3 +597,117 @@ impl Table {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assets::table::{
+            lights::LightBind,
+            physics::{HitTrigger, RollTrigger},
+            script::EffectBind,
+        },
+        config::TableId,
+        table::{scenario::Scenario, tasks::TaskKind},
+    };
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn burnin_targets() {
+        let mut s = Scenario::start(TableId::Table2);
+        s.set_lights(LightBind::SpeedBur, false);
+        s.set_lights(LightBind::SpeedGear, false);
+        s.table.speed.blink_bur = [false; 3];
+        let score = s.score();
+        s.hit(HitTrigger::SpeedBur(0));
+        assert!(s.lit(LightBind::SpeedBur, 0));
+        assert!(s.task_queued(TaskKind::SpeedUnblinkBur(0)));
+        // Blinking targets don't count.
+        s.hit(HitTrigger::SpeedBur(0));
+        assert_eq!(s.score(), score + 7510);
+
+        let jackpot = s.table.score_jackpot;
+        s.hit(HitTrigger::SpeedBur(1));
+        s.hit(HitTrigger::SpeedBur(2));
+        assert_eq!(s.score(), score + 3 * 7510);
+        assert_eq!(
+            s.table.score_jackpot,
+            jackpot + s.table.assets.score_jackpot_incr
+        );
+        assert!(s.task_queued(TaskKind::SpeedUnblinkBurAll));
+        assert!(s.lit(LightBind::SpeedGear, 2));
+        assert!(!s.effect_fired(EffectBind::SpeedGear));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn gears() {
+        let mut s = Scenario::start(TableId::Table2);
+        s.set_lights(LightBind::SpeedGear, true);
+        s.set_light(LightBind::SpeedGear, 3, false);
+        s.set_lights(LightBind::SpeedNin, true);
+        s.set_light(LightBind::SpeedNin, 2, false);
+        s.table.speed.blink_nin = [false; 3];
+        s.table.speed.max_place = 0;
+        s.table.speed.cur_gear = 0;
+        s.hit(HitTrigger::SpeedNin(2));
+        assert!(s.effect_fired(EffectBind::SpeedGear));
+        assert_eq!(s.table.speed.max_place, 2);
+        assert_eq!(s.table.speed.cur_gear, 1);
+        assert!(s.lit(LightBind::SpeedGearNum, 0));
+        for which in 0..4 {
+            assert!(!s.lit(LightBind::SpeedGear, which));
+        }
+        assert!(s.task_queued(TaskKind::SpeedUnblinkGearAll));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn pit_lanes() {
+        let mut s = Scenario::start(TableId::Table2);
+        s.set_lights(LightBind::SpeedPit, false);
+        s.set_light(LightBind::SpeedOffroadMultiBonus, 0, false);
+        s.table.speed.mb_active = 0;
+        s.table.speed.mb_pending = 0;
+        s.table.speed.timeout_pit = [0; 3];
+        s.table.speed.timeout_pit_all = 0;
+        s.roll(RollTrigger::SpeedPit(0));
+        assert!(s.effect_fired(EffectBind::SpeedPit));
+        assert_eq!(s.table.speed.timeout_pit[0], 20);
+        s.roll(RollTrigger::SpeedPit(1));
+        s.roll(RollTrigger::SpeedPit(2));
+        assert!(s.effect_fired(EffectBind::SpeedPitAll));
+        assert_eq!(s.table.speed.mb_pending, 1);
+        assert!(s.lit(LightBind::SpeedOffroadMultiBonus, 0));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn pit_stop() {
+        let mut s = Scenario::start(TableId::Table2);
+        s.set_light(LightBind::SpeedPitStopSuperJackpot, 0, false);
+        s.set_light(LightBind::SpeedPitStopGoal, 0, false);
+        s.set_light(LightBind::SpeedPitStopHoldBonus, 0, true);
+        s.roll(RollTrigger::SpeedPitStop);
+        assert!(s.table.hold_bonus);
+        assert!(s.effect_fired(EffectBind::SpeedHoldBonus));
+        assert!(!s.lit(LightBind::SpeedPitStopHoldBonus, 0));
+        assert!(s.task_queued(TaskKind::SpeedPitStop(20)));
+        assert!(s.table.ball.frozen);
+        // The ball is kicked back out.
+        assert!(s.run_until(60, |s| !s.table.ball.frozen));
+        assert!(s.table.ball.speed != (0, 0));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn pit_loop_extra_ball() {
+        let mut s = Scenario::start(TableId::Table2);
+        s.set_light(LightBind::SpeedPitLoopExtraBall, 0, true);
+        s.roll(RollTrigger::SpeedPitLoopPre);
+        s.roll(RollTrigger::SpeedPitLoopJump);
+        assert!(s.effect_fired(EffectBind::SpeedExtraBall));
+        assert_eq!(s.table.extra_balls, 1);
+        assert!(!s.lit(LightBind::SpeedPitLoopExtraBall, 0));
+        assert!(s.lit(LightBind::SpeedExtraBall, 0));
+    }
+}
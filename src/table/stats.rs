@@ -0,0 +1,120 @@
+//! Lifetime gameplay statistics.  Counters are collected per game and only added to the
+//! table's statistics when the game ends normally, so aborted and practice games don't count.
+
+use crate::{
+    assets::table::physics::RollTrigger,
+    config::{stat_rolls, TableStats},
+    view::Action,
+};
+
+use super::Table;
+
+#[derive(Debug, Default)]
+pub struct StatsState {
+    lifetime: TableStats,
+    /// `None` outside of a counted game.
+    game: Option<TableStats>,
+    in_mode: bool,
+    /// Whether a jackpot was collected during the current mode, or the last one once it ended.
+    mode_won: bool,
+    changed: bool,
+}
+
+impl StatsState {
+    pub fn new(lifetime: TableStats) -> Self {
+        Self {
+            lifetime,
+            ..Default::default()
+        }
+    }
+}
+
+impl Table {
+    pub fn stat(&mut self, f: impl FnOnce(&mut TableStats)) {
+        if let Some(game) = &mut self.stats.game {
+            f(game);
+        }
+    }
+
+    pub fn stats_game_start(&mut self) {
+        self.stats.in_mode = false;
+        self.stats.mode_won = false;
+        self.stats.game = if self.game_on_record() {
+            Some(TableStats::default())
+        } else {
+            None
+        };
+    }
+
+    pub fn stats_game_abort(&mut self) {
+        self.stats.game = None;
+    }
+
+    pub fn stats_game_over(&mut self) {
+        let Some(game) = self.stats.game.take() else {
+            return;
+        };
+        let stats = &mut self.stats.lifetime;
+        for player in &self.players {
+            stats.games += 1;
+            stats.total_score += player.score_main.to_u64();
+            stats.best_score = stats.best_score.max(player.score_main);
+        }
+        stats.balls_drained += game.balls_drained;
+        stats.extra_balls += game.extra_balls;
+        stats.tilts += game.tilts;
+        stats.jackpots += game.jackpots;
+        stats.modes_started += game.modes_started;
+        stats.modes_completed += game.modes_completed;
+        stats.match_wins += game.match_wins;
+        for (total, count) in stats.rolls.iter_mut().zip(game.rolls) {
+            *total += count;
+        }
+        self.stats.changed = true;
+    }
+
+    pub fn stats_roll_trigger(&mut self, kind: RollTrigger) {
+        if let Some(idx) = stat_rolls(self.assets.table)
+            .iter()
+            .position(|&k| k == kind)
+        {
+            self.stat(|stats| stats.rolls[idx] += 1);
+        }
+    }
+
+    /// Modes always run until they time out, and what the rules reward during one is its
+    /// jackpot, so a mode counts as completed once a jackpot is collected while it runs.
+    pub fn stats_jackpot(&mut self) {
+        self.stat(|stats| stats.jackpots += 1);
+        if self.in_mode && !self.stats.mode_won {
+            self.stats.mode_won = true;
+            self.stat(|stats| stats.modes_completed += 1);
+        }
+    }
+
+    /// Modes are started all over the table code, so they are counted by watching the mode
+    /// flag.
+    pub(super) fn stats_frame(&mut self) {
+        if self.in_mode == self.stats.in_mode {
+            return;
+        }
+        self.stats.in_mode = self.in_mode;
+        if self.in_mode {
+            self.stats.mode_won = false;
+            self.stat(|stats| stats.modes_started += 1);
+        }
+    }
+
+    /// Whether the current or last mode was completed, as counted in the statistics.
+    pub(super) fn mode_won(&self) -> bool {
+        self.stats.mode_won
+    }
+
+    pub(super) fn stats_flush(&mut self) -> Option<Action> {
+        if !self.stats.changed {
+            return None;
+        }
+        self.stats.changed = false;
+        Some(Action::SaveStats(self.assets.table, self.stats.lifetime))
+    }
+}
@@ -8,8 +8,14 @@ use crate::{
     bcd::Bcd,
 };
 
-use super::{tasks::TaskKind, Table};
+use super::{
+    breakdown::ScoreSource,
+    feedback::{Coil, Kicker},
+    tasks::TaskKind,
+    Table,
+};
 
+#[derive(Debug)]
 pub struct StonesState {
     pub flipper_lock_key: bool,
     pub flipper_lock_rip: bool,
@@ -292,7 +298,11 @@ impl Table {
         }
         self.mode_count_hit();
         self.play_sfx_bind(SfxBind::StonesHitStone);
-        self.score_premult(Bcd::from_ascii(b"17520"), Bcd::from_ascii(b"750"));
+        self.score_premult(
+            ScoreSource::Award("STONE"),
+            Bcd::from_ascii(b"17520"),
+            Bcd::from_ascii(b"750"),
+        );
         self.light_set(LightBind::StonesStone, which, true);
         if self.light_all_lit(LightBind::StonesStone) && self.light_all_lit(LightBind::StonesBone) {
             self.stones_stones_bones_all();
@@ -312,7 +322,11 @@ impl Table {
         }
         self.mode_count_hit();
         self.play_sfx_bind(SfxBind::StonesHitBone);
-        self.score_premult(Bcd::from_ascii(b"27530"), Bcd::from_ascii(b"510"));
+        self.score_premult(
+            ScoreSource::Award("BONE"),
+            Bcd::from_ascii(b"27530"),
+            Bcd::from_ascii(b"510"),
+        );
         self.light_set(LightBind::StonesBone, which, true);
         if self.light_all_lit(LightBind::StonesStone) && self.light_all_lit(LightBind::StonesBone) {
             self.stones_stones_bones_all();
@@ -327,13 +341,21 @@ impl Table {
         self.raise_physmap(PhysmapBind::StonesGateRampTower);
         if self.stones.million_plus {
             self.stones.score_million_plus += Bcd::from_ascii(b"1000000");
-            self.score(self.stones.score_million_plus, Bcd::ZERO);
+            self.score(
+                ScoreSource::Award("MILLION PLUS"),
+                self.stones.score_million_plus,
+                Bcd::ZERO,
+            );
             self.effect(EffectBind::StonesMillionPlus);
             self.light_set(LightBind::StonesMillionPlus, 0, false);
             self.stones.million_plus = false;
         }
         self.stones.ball_locked = false;
-        self.score(Bcd::from_ascii(b"10000"), Bcd::from_ascii(b"1000"));
+        self.score(
+            ScoreSource::Award("KEY ENTRY"),
+            Bcd::from_ascii(b"10000"),
+            Bcd::from_ascii(b"1000"),
+        );
     }
 
     pub fn stones_roll_key(&mut self, which: u8) {
@@ -341,11 +363,16 @@ impl Table {
             return;
         }
         self.play_sfx_bind(SfxBind::RollTrigger);
-        self.score_premult(Bcd::from_ascii(b"10060"), Bcd::from_ascii(b"1010"));
+        self.score_premult(
+            ScoreSource::Award("KEY"),
+            Bcd::from_ascii(b"10060"),
+            Bcd::from_ascii(b"1010"),
+        );
         self.light_set(LightBind::StonesKey, which, true);
         if let Some(target) = self.stones.key_skillshot {
             if which == target {
                 self.stones.score_skill_shot += Bcd::from_ascii(b"1000000");
+                self.credit(ScoreSource::SkillShot, self.stones.score_skill_shot);
                 self.score_main += self.stones.score_skill_shot;
                 self.effect(EffectBind::StonesSkillShot);
                 self.stones_incr_vault();
@@ -477,8 +504,10 @@ impl Table {
             self.stones.tower_jackpot = false;
             self.light_set(LightBind::StonesTowerJackpot, 0, false);
             self.effect_force(EffectBind::StonesTowerJackpot);
+            self.credit(ScoreSource::Jackpot, self.score_jackpot);
             self.score_main += self.score_jackpot;
             self.score_jackpot = self.assets.score_jackpot_init;
+            self.stats_jackpot();
             visible_effect = true;
             self.silence_effect = true;
             self.stones.tower_super_jackpot = true;
@@ -525,7 +554,11 @@ impl Table {
             self.silence_effect = true;
         } else {
             visible_effect |= self.effect(EffectBind::StonesTowerBonus);
-            self.score(self.stones.score_tower_bonus, Bcd::ZERO);
+            self.score(
+                ScoreSource::Award("TOWER BONUS"),
+                self.stones.score_tower_bonus,
+                Bcd::ZERO,
+            );
             self.stones.score_tower_bonus = Bcd::from_ascii(b"1000000");
             if self.stones.tower_hunt {
                 self.stones_tower_open();
@@ -577,6 +610,7 @@ impl Table {
     pub fn stones_tower_eject(&mut self) {
         self.play_sfx_bind(SfxBind::StonesEject);
         self.ball.teleport(Layer::Overhead, (141, 143), (0, -3333));
+        self.fire(Coil::Kicker(Kicker::StonesTower));
         self.stones.in_tower = false;
     }
 
@@ -591,6 +625,7 @@ impl Table {
         self.mode_count_ramp();
         self.incr_jackpot();
         let mut visible_effect = false;
+        self.credit(ScoreSource::Award("WELL"), self.stones.score_well);
         self.score_main += self.stones.score_well;
         if self.stones.lock_well_ready {
             self.stones.ball_locked = true;
@@ -598,6 +633,7 @@ impl Table {
             visible_effect |= self.effect(EffectBind::StonesLock);
             self.silence_effect = true;
             self.ball.teleport(Layer::Ground, (300, 530), (10, 0));
+            self.fire(Coil::Trough);
             self.special_plunger_event = true;
             self.stones.in_well = false;
             self.set_music_plunger();
@@ -649,6 +685,7 @@ impl Table {
             visible_effect |= self.effect(EffectBind::StonesLock);
             self.silence_effect = true;
             self.ball.teleport(Layer::Ground, (300, 530), (10, 0));
+            self.fire(Coil::Trough);
             self.special_plunger_event = true;
             self.stones.in_vault = false;
             self.set_music_plunger();
@@ -742,6 +779,7 @@ impl Table {
             }
         }
         self.mode_count_ramp();
+        self.credit(ScoreSource::Award("VAULT"), self.stones.score_vault);
         self.score_main += self.stones.score_vault;
         if visible_effect {
             self.silence_effect = true;
@@ -764,7 +802,11 @@ impl Table {
             self.effect(EffectBind::StonesTopMillion);
         } else {
             self.play_sfx_bind(SfxBind::RollTrigger);
-            self.score_premult(Bcd::from_ascii(b"10030"), Bcd::from_ascii(b"1020"));
+            self.score_premult(
+                ScoreSource::Award("TOP RAMP"),
+                Bcd::from_ascii(b"10030"),
+                Bcd::from_ascii(b"1020"),
+            );
         }
         self.stones.timeout_top_loop = 300;
     }
@@ -774,7 +816,11 @@ impl Table {
             return;
         }
         self.play_sfx_bind(SfxBind::RollTrigger);
-        self.score_premult(Bcd::from_ascii(b"10070"), Bcd::from_ascii(b"1080"));
+        self.score_premult(
+            ScoreSource::Award("RIP"),
+            Bcd::from_ascii(b"10070"),
+            Bcd::from_ascii(b"1080"),
+        );
         self.light_set(LightBind::StonesRip, which, true);
         if self.light_all_lit(LightBind::StonesRip) {
             self.stones.rip_blinking = true;
@@ -798,7 +844,11 @@ impl Table {
 
     pub fn stones_ramp_screams(&mut self) {
         self.play_sfx_bind(SfxBind::RollTrigger);
-        self.score_premult(Bcd::from_ascii(b"10060"), Bcd::from_ascii(b"1050"));
+        self.score_premult(
+            ScoreSource::Award("SCREAMS"),
+            Bcd::from_ascii(b"10060"),
+            Bcd::from_ascii(b"1050"),
+        );
         if self.stones.scream_demon {
             self.stones.scream_demon = false;
             self.light_set(LightBind::StonesScreamDemon, 0, false);
@@ -870,7 +920,11 @@ impl Table {
         self.mode_count_ramp();
         self.stones_incr_vault();
         self.play_sfx_bind(SfxBind::RollTrigger);
-        self.score_premult(Bcd::from_ascii(b"10030"), Bcd::from_ascii(b"1040"));
+        self.score_premult(
+            ScoreSource::Award("LEFT RAMP"),
+            Bcd::from_ascii(b"10030"),
+            Bcd::from_ascii(b"1040"),
+        );
         if !self.stones.million_plus {
             self.stones.million_plus = true;
             self.light_blink(
@@ -911,7 +965,11 @@ impl Table {
         }
         self.mode_count_ramp();
         self.play_sfx_bind(SfxBind::RollTrigger);
-        self.score_premult(Bcd::from_ascii(b"10020"), Bcd::from_ascii(b"1010"));
+        self.score_premult(
+            ScoreSource::Award("LEFT RAMP"),
+            Bcd::from_ascii(b"10020"),
+            Bcd::from_ascii(b"1010"),
+        );
     }
 
     pub fn stones_incr_vault(&mut self) {
@@ -940,3 +998,100 @@ impl Table {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        assets::table::{
+            lights::LightBind,
+            physics::{HitTrigger, RollTrigger},
+            script::EffectBind,
+        },
+        config::TableId,
+        table::{scenario::Scenario, tasks::TaskKind},
+    };
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn stone_target() {
+        let mut s = Scenario::start(TableId::Table4);
+        s.set_lights(LightBind::StonesStone, false);
+        let score = s.score();
+        s.hit(HitTrigger::StonesStone(1));
+        assert_eq!(s.score(), score + 17520);
+        assert!(s.lit(LightBind::StonesStone, 1));
+        assert!(s.task_queued(TaskKind::StonesUnblinkStone(1)));
+        // Blinking targets don't count.
+        s.hit(HitTrigger::StonesStone(1));
+        assert_eq!(s.score(), score + 17520);
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn stones_and_bones_light_ghost() {
+        let mut s = Scenario::start(TableId::Table4);
+        s.set_lights(LightBind::StonesStone, true);
+        s.set_light(LightBind::StonesStone, 0, false);
+        s.set_lights(LightBind::StonesBone, true);
+        s.table.stones.ghost_active = false;
+        s.table.stones.cur_ghost = 0;
+        let score = s.score();
+        s.hit(HitTrigger::StonesStone(0));
+        assert!(s.score() >= score + 17520);
+        assert!(s.effect_fired(EffectBind::StonesGhostLit0));
+        assert!(s.table.stones.ghost_active);
+        assert!(s.task_queued(TaskKind::StonesUnblinkStonesBones));
+        // Everything blinks for a while, and the targets don't count.
+        let score = s.score();
+        s.hit(HitTrigger::StonesBone(0));
+        assert_eq!(s.score(), score);
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn keys_open_tower() {
+        let mut s = Scenario::start(TableId::Table4);
+        s.set_lights(LightBind::StonesKey, false);
+        s.table.stones.key_skillshot = None;
+        s.table.stones.key_tower_cycle = 0;
+        s.table.stones.tower_1m = false;
+        s.table.stones.tower_open = false;
+        let score = s.score();
+        s.roll(RollTrigger::StonesKey(0));
+        assert_eq!(s.score(), score + 10060);
+        assert!(s.task_queued(TaskKind::StonesUnblinkKey(0)));
+        s.roll(RollTrigger::StonesKey(1));
+        s.roll(RollTrigger::StonesKey(2));
+        assert!(s.effect_fired(EffectBind::StonesTowerOpen));
+        assert!(s.table.stones.tower_open);
+        assert!(s.table.stones.tower_1m);
+        assert_eq!(s.table.stones.key_tower_cycle, 1);
+        assert!(s.task_queued(TaskKind::StonesUnblinkKeyAll));
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn key_skill_shot() {
+        let mut s = Scenario::start(TableId::Table4);
+        s.set_lights(LightBind::StonesKey, false);
+        s.table.stones.key_skillshot = Some(1);
+        let score = s.score();
+        s.roll(RollTrigger::StonesKey(1));
+        assert!(s.effect_fired(EffectBind::StonesSkillShot));
+        assert!(s.score() >= score + 1_010_060);
+        assert_eq!(s.table.stones.key_skillshot, None);
+    }
+
+    #[test]
+    #[ignore = "needs the game data in PFR_DATA"]
+    fn well_lock() {
+        let mut s = Scenario::start(TableId::Table4);
+        s.set_light(LightBind::StonesWellLock, 0, false);
+        s.table.stones.lock_well_ready = true;
+        s.roll(RollTrigger::StonesWell);
+        assert!(s.effect_fired(EffectBind::StonesLock));
+        assert!(s.table.stones.ball_locked);
+        assert!(s.lit(LightBind::StonesWellLock, 0));
+        assert!(!s.table.stones.lock_well_ready);
+    }
+}
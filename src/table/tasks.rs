@@ -11,7 +11,13 @@ use crate::{
     config::TableId,
 };
 
-use super::{show::PrizeState, KbdState, Table};
+use super::{
+    breakdown::ScoreSource,
+    events::GameEvent,
+    feedback::{Coil, Kicker},
+    show::PrizeState,
+    KbdState, Table,
+};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum TaskKind {
@@ -93,12 +99,21 @@ pub enum TaskKind {
     StonesScreamExtra,
 }
 
+#[derive(Debug)]
 pub struct Task {
     timer: u16,
     kind: TaskKind,
 }
 
 impl Task {
+    pub fn timer(&self) -> u16 {
+        self.timer
+    }
+
+    pub fn kind(&self) -> TaskKind {
+        self.kind
+    }
+
     pub fn run(&mut self, table: &mut Table) -> bool {
         if self.timer != self.kind.delay(table) {
             self.timer += 1;
@@ -117,6 +132,9 @@ impl Task {
             TaskKind::IssueBallRaiseSfx => table.play_sfx_bind(SfxBind::RaiseHitTargets),
             TaskKind::DrainSfx => table.play_sfx_bind(SfxBind::BallDrained),
             TaskKind::GameOver => {
+                table.stats_game_over();
+                table.breakdown_game_over();
+                table.emit(GameEvent::GameOver);
                 table.kbd_state = KbdState::Main;
                 table.in_attract = true;
                 table.lights.reset();
@@ -134,13 +152,12 @@ impl Task {
             }
             TaskKind::PartyDropZoneRelease => {
                 table.scroll.reset_special_target();
-                table.ball.teleport(
-                    Layer::Overhead,
-                    (15, 47),
-                    (0, rand::thread_rng().gen_range(0..0x80)),
-                );
+                table
+                    .ball
+                    .teleport(Layer::Overhead, (15, 47), (0, table.rng.gen_range(0..0x80)));
                 table.play_sfx_bind(SfxBind::IssueBall);
                 table.light_set_all(LightBind::PartyDrop, false);
+                table.fire(Coil::Kicker(Kicker::PartyDropZone));
             }
             TaskKind::PartyDropZoneScroll(ref mut pos) => {
                 if *pos >= 5 {
@@ -233,6 +250,7 @@ impl Task {
             }
             TaskKind::PartySnacksRelease => {
                 table.play_sfx_bind(SfxBind::PartySnacksRelease);
+                table.fire(Coil::Kicker(Kicker::PartySnacks));
                 table.ball.teleport(Layer::Overhead, (3, 253), (0, -2500));
                 table.add_task(TaskKind::PartySnacksFinish);
             }
@@ -245,6 +263,7 @@ impl Task {
                 table.light_set(LightBind::PartyDemonHead, 0, false);
                 table.play_sfx_bind(SfxBind::IssueBall);
                 table.ball.teleport(Layer::Ground, (257, 310), (-575, 1575));
+                table.fire(Coil::Kicker(Kicker::PartyDemon));
                 table.party.in_demon = false;
             }
             TaskKind::PartySideExtraBallFinish => table.block_drain = false,
@@ -328,6 +347,7 @@ impl Task {
             }
             TaskKind::SpeedPitStop(_) => {
                 table.play_sfx_bind(SfxBind::SpeedEjectPit);
+                table.fire(Coil::Kicker(Kicker::SpeedPitStop));
                 table.ball.teleport(Layer::Ground, (256, 41), (-2100, 800));
             }
             TaskKind::SpeedUnblinkCar => {
@@ -356,6 +376,7 @@ impl Task {
                 table.play_sfx_bind(SfxBind::IssueBall);
                 table.ball.frozen = false;
                 table.ball.speed.1 = -3500;
+                table.fire(Coil::Kicker(Kicker::ShowVault));
             }
             TaskKind::ShowBillionRelease => {
                 table.light_set(LightBind::ShowBillion, 0, false);
@@ -365,13 +386,17 @@ impl Task {
                 table.play_sfx_bind(SfxBind::IssueBall);
                 table.ball.frozen = false;
                 table.ball.speed.1 = -3500;
+                table.fire(Coil::Kicker(Kicker::ShowVault));
             }
             TaskKind::ShowSpinWheelEnd => {
                 table.scroll.reset_special_target();
-                table.score_main += table.show_wheel_score();
+                let score = table.show_wheel_score();
+                table.credit(ScoreSource::Award("SPIN WHEEL"), score);
+                table.score_main += score;
                 table.start_script(ScriptBind::ShowSpinWheelClear);
                 table.ball.frozen = false;
                 table.ball.speed.1 = -2916;
+                table.fire(Coil::Kicker(Kicker::ShowVault));
                 table.light_set(LightBind::ShowSpinWheel, 0, false);
             }
             TaskKind::ShowGivePrize => {
@@ -432,11 +457,13 @@ impl Task {
             TaskKind::StonesWellEject => {
                 table.play_sfx_bind(SfxBind::StonesEject);
                 table.ball.teleport(Layer::Ground, (275, 245), (-666, 1666));
+                table.fire(Coil::Kicker(Kicker::StonesWell));
                 table.stones.in_well = false;
             }
             TaskKind::StonesVaultEject => {
                 table.play_sfx_bind(SfxBind::StonesEject);
                 table.drop_physmap(PhysmapBind::StonesGateKickback);
+                table.fire(Coil::Kicker(Kicker::StonesVault));
                 table.ball.teleport(Layer::Ground, (2, 532), (0, -2880));
                 table.stones.in_vault = false;
                 table.add_task(TaskKind::StonesRaiseKickback);
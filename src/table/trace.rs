@@ -0,0 +1,224 @@
+//! Ball position recording.  Once enabled, the ball is sampled on every physics frame while it
+//! is in play, building a heatmap of each layer and, optionally, a full trajectory log.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use enum_map::{enum_map, EnumMap};
+use ndarray::Array2;
+use serde::Serialize;
+
+use crate::{
+    assets::table::{
+        physics::{Layer, RollTrigger},
+        Assets,
+    },
+    config::TableId,
+};
+
+use super::Table;
+
+// Each sample is spread over a square this far around the ball's centre, so the heatmap
+// shows the paths rather than single pixels.
+const HEAT_RADIUS: i16 = 2;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TrajectoryFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TraceSample {
+    /// Physics frames since recording started.
+    pub frame: u64,
+    pub layer: Layer,
+    pub pos_hires: (i32, i32),
+    pub speed: (i16, i16),
+    /// Roll triggers crossed since the previous sample.
+    pub triggers: Vec<RollTrigger>,
+}
+
+#[derive(Debug)]
+pub struct Trace {
+    assets: Arc<Assets>,
+    frame: u64,
+    heat: EnumMap<Layer, Array2<u32>>,
+    samples: Option<Vec<TraceSample>>,
+    triggers: Vec<RollTrigger>,
+}
+
+impl Trace {
+    pub fn table(&self) -> TableId {
+        self.assets.table
+    }
+
+    pub fn samples(&self) -> Option<&[TraceSample]> {
+        self.samples.as_deref()
+    }
+
+    /// Writes the heatmap of a layer as an RGB PNG, drawn over a darkened main board.  Colors
+    /// go from blue to red on a log scale.
+    pub fn write_heatmap(&self, layer: Layer, w: impl Write) -> Result<(), png::EncodingError> {
+        let heat = &self.heat[layer];
+        let (width, height) = heat.dim();
+        let max = heat.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let board = &self.assets.main_board;
+        let mut data = Vec::with_capacity(width * height * 3);
+        for y in 0..height {
+            for x in 0..width {
+                let (r, g, b) = board.cmap[board.data[(x, y)] as usize];
+                let gray = (r as f32 * 0.3 + g as f32 * 0.59 + b as f32 * 0.11) * 0.5;
+                let count = heat[(x, y)];
+                let pixel = if count == 0 {
+                    [gray; 3]
+                } else {
+                    let t = (count as f32).ln_1p() / max.ln_1p();
+                    let color = [
+                        255.0 * (2.0 * t - 0.5).clamp(0.0, 1.0),
+                        255.0 * (1.0 - (2.0 * t - 1.0).abs()),
+                        255.0 * (1.0 - 2.0 * t).clamp(0.0, 1.0),
+                    ];
+                    let alpha = 0.4 + 0.6 * t;
+                    color.map(|c| gray * (1.0 - alpha) + c * alpha)
+                };
+                data.extend(pixel.map(|c| c as u8));
+            }
+        }
+        let mut encoder = png::Encoder::new(w, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)
+    }
+
+    /// One line per sample, with the crossed triggers separated by spaces in the last column.
+    pub fn write_csv(&self, mut w: impl Write) -> std::io::Result<()> {
+        writeln!(w, "frame,layer,x,y,speed_x,speed_y,triggers")?;
+        for sample in self.samples.iter().flatten() {
+            let triggers: Vec<_> = sample.triggers.iter().map(|t| format!("{t:?}")).collect();
+            writeln!(
+                w,
+                "{},{:?},{},{},{},{},{}",
+                sample.frame,
+                sample.layer,
+                sample.pos_hires.0,
+                sample.pos_hires.1,
+                sample.speed.0,
+                sample.speed.1,
+                triggers.join(" "),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn write_json(&self, w: impl Write) -> serde_json::Result<()> {
+        serde_json::to_writer(w, self.samples().unwrap_or_default())
+    }
+
+    /// Writes `tableN-ground.png` and `tableN-overhead.png` to `dir`, creating it if needed,
+    /// and `tableN-trajectory.csv` or `.json` if a `trajectory` format is given.
+    pub fn save(&self, dir: &Path, trajectory: Option<TrajectoryFormat>) -> Result<(), String> {
+        let prefix = match self.table() {
+            TableId::Table1 => "table1",
+            TableId::Table2 => "table2",
+            TableId::Table3 => "table3",
+            TableId::Table4 => "table4",
+        };
+        std::fs::create_dir_all(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+        let create = |name: String| {
+            let path = dir.join(name);
+            File::create(&path)
+                .map(BufWriter::new)
+                .map_err(|e| format!("{}: {e}", path.display()))
+                .map(|file| (path, file))
+        };
+        for (layer, name) in [(Layer::Ground, "ground"), (Layer::Overhead, "overhead")] {
+            let (path, file) = create(format!("{prefix}-{name}.png"))?;
+            self.write_heatmap(layer, file)
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+        let Some(trajectory) = trajectory else {
+            return Ok(());
+        };
+        let ext = match trajectory {
+            TrajectoryFormat::Csv => "csv",
+            TrajectoryFormat::Json => "json",
+        };
+        let (path, file) = create(format!("{prefix}-trajectory.{ext}"))?;
+        match trajectory {
+            TrajectoryFormat::Csv => self.write_csv(file).map_err(|e| e.to_string()),
+            TrajectoryFormat::Json => self.write_json(file).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+impl Table {
+    /// Starts recording the ball.  With `trajectory`, every sample is kept, not just the
+    /// heatmap.
+    pub fn set_trace(&mut self, trajectory: bool) {
+        let (width, height) = self.assets.main_board.data.dim();
+        self.trace = Some(Trace {
+            assets: self.assets.clone(),
+            frame: 0,
+            heat: enum_map! { _ => Array2::zeros((width, height)) },
+            samples: trajectory.then(Vec::new),
+            triggers: Vec::new(),
+        });
+    }
+
+    /// Goes on recording into a trace taken from an earlier visit to this table, so that a
+    /// whole session adds up to one heatmap and trajectory.
+    pub fn resume_trace(&mut self, mut trace: Trace) {
+        assert_eq!(trace.table(), self.assets.table);
+        trace.triggers.clear();
+        self.trace = Some(trace);
+    }
+
+    pub(super) fn trace_frame(&mut self) {
+        let Some(trace) = &mut self.trace else {
+            return;
+        };
+        trace.frame += 1;
+        if self.in_attract || self.ball.frozen {
+            // Triggers crossed without a sample don't belong to the next one.
+            trace.triggers.clear();
+            return;
+        }
+        let heat = &mut trace.heat[self.ball.layer];
+        let (width, height) = heat.dim();
+        let (cx, cy) = self.ball.pos_center();
+        for y in cy - HEAT_RADIUS..=cy + HEAT_RADIUS {
+            for x in cx - HEAT_RADIUS..=cx + HEAT_RADIUS {
+                if (0..width as i16).contains(&x) && (0..height as i16).contains(&y) {
+                    heat[(x as usize, y as usize)] += 1;
+                }
+            }
+        }
+        if let Some(samples) = &mut trace.samples {
+            samples.push(TraceSample {
+                frame: trace.frame,
+                layer: self.ball.layer,
+                pos_hires: self.ball.pos_hires,
+                speed: self.ball.speed,
+                triggers: std::mem::take(&mut trace.triggers),
+            });
+        }
+    }
+
+    pub(super) fn trace_roll_trigger(&mut self, kind: RollTrigger) {
+        if self.in_attract || self.ball.frozen {
+            return;
+        }
+        if let Some(trace) = &mut self.trace {
+            if trace.samples.is_some() {
+                trace.triggers.push(kind);
+            }
+        }
+    }
+}
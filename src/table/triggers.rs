@@ -8,7 +8,7 @@ use crate::{
     config::TableId,
 };
 
-use super::Table;
+use super::{breakdown::ScoreSource, events::GameEvent, Table};
 
 impl Table {
     pub fn do_hit_triggers(&mut self) {
@@ -24,22 +24,27 @@ impl Table {
         }
         for area in &self.assets.hit_triggers {
             if area.rect.contains(hit_pos) {
-                match area.kind {
-                    HitTrigger::PartyArcadeButton => self.party_arcade_button(),
-                    HitTrigger::PartyDuck(which) => self.party_hit_duck(which),
-                    HitTrigger::SpeedBur(which) => self.speed_hit_bur(which),
-                    HitTrigger::SpeedNin(which) => self.speed_hit_nin(which),
-                    HitTrigger::ShowDollar(which) => self.show_hit_dollar(which),
-                    HitTrigger::ShowCenter(which) => self.show_hit_center(which),
-                    HitTrigger::ShowLeft(which) => self.show_hit_left(which),
-                    HitTrigger::StonesBone(which) => self.stones_hit_bone(which),
-                    HitTrigger::StonesStone(which) => self.stones_hit_stone(which),
-                }
+                self.do_hit_trigger(area.kind);
                 return;
             }
         }
     }
 
+    pub fn do_hit_trigger(&mut self, kind: HitTrigger) {
+        self.emit(GameEvent::HitTrigger { trigger: kind });
+        match kind {
+            HitTrigger::PartyArcadeButton => self.party_arcade_button(),
+            HitTrigger::PartyDuck(which) => self.party_hit_duck(which),
+            HitTrigger::SpeedBur(which) => self.speed_hit_bur(which),
+            HitTrigger::SpeedNin(which) => self.speed_hit_nin(which),
+            HitTrigger::ShowDollar(which) => self.show_hit_dollar(which),
+            HitTrigger::ShowCenter(which) => self.show_hit_center(which),
+            HitTrigger::ShowLeft(which) => self.show_hit_left(which),
+            HitTrigger::StonesBone(which) => self.stones_hit_bone(which),
+            HitTrigger::StonesStone(which) => self.stones_hit_stone(which),
+        }
+    }
+
     pub fn do_roll_triggers(&mut self) {
         let pos = self.ball_center();
         let list = if self.tilted {
@@ -61,6 +66,9 @@ impl Table {
     }
 
     pub fn do_roll_trigger(&mut self, kind: RollTrigger) {
+        self.stats_roll_trigger(kind);
+        self.trace_roll_trigger(kind);
+        self.emit(GameEvent::RollTrigger { trigger: kind });
         match kind {
             RollTrigger::Dummy => (),
             RollTrigger::PlungerBottom => self.at_spring = true,
@@ -203,15 +211,27 @@ impl Table {
             RollTrigger::ShowRampTopSecondary => self.incr_jackpot(),
             RollTrigger::StonesLaneInnerLeft | RollTrigger::StonesLaneInnerRight => {
                 self.play_sfx_bind(SfxBind::RollInner);
-                self.score_premult(Bcd::from_ascii(b"10070"), Bcd::from_ascii(b"1080"));
+                self.score_premult(
+                    ScoreSource::Award("INNER LANE"),
+                    Bcd::from_ascii(b"10070"),
+                    Bcd::from_ascii(b"1080"),
+                );
             }
             RollTrigger::StonesLaneOuterLeft => {
                 self.play_sfx_bind(SfxBind::RollTrigger);
-                self.score(Bcd::from_ascii(b"500010"), Bcd::ZERO);
+                self.score(
+                    ScoreSource::Award("OUTER LANE"),
+                    Bcd::from_ascii(b"500010"),
+                    Bcd::ZERO,
+                );
             }
             RollTrigger::StonesLaneOuterRight => {
                 self.play_sfx_bind(SfxBind::RollTrigger);
-                self.score(Bcd::from_ascii(b"500030"), Bcd::ZERO);
+                self.score(
+                    ScoreSource::Award("OUTER LANE"),
+                    Bcd::from_ascii(b"500030"),
+                    Bcd::ZERO,
+                );
             }
             RollTrigger::StonesKeyEntry => self.stones_roll_key_entry(),
             RollTrigger::StonesRampTower => {
@@ -1,9 +1,10 @@
 use arrayvec::ArrayVec;
-use winit::event::{ElementState, TouchPhase};
+use winit::event::{ElementState, MouseButton, TouchPhase};
 use winit::keyboard::KeyCode;
 
-use crate::config::{HighScore, Options, TableId};
+use crate::config::{HighScoreTable, Options, PracticeShot, TableId, TableStats, PRACTICE_SLOTS};
 use crate::icons::IconKind;
+use crate::table::{TableInfo, Trace};
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Route {
@@ -11,13 +12,15 @@ pub enum Route {
     Table(TableId),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Action {
     None,
     Navigate(Route),
     Exit,
     SaveOptions(Options),
-    SaveHighScores(TableId, [HighScore; 4]),
+    SaveHighScores(TableId, Box<HighScoreTable>),
+    SavePracticeShots(TableId, [Option<PracticeShot>; PRACTICE_SLOTS]),
+    SaveStats(TableId, TableStats),
 }
 
 pub trait View {
@@ -28,5 +31,33 @@ pub trait View {
     fn handle_touch(&mut self, id: u64, phase: TouchPhase, pos: (i32, i32));
     fn handle_key(&mut self, key: KeyCode, state: ElementState);
     fn handle_touch_icon(&mut self, icon: IconKind);
+    /// Returns `false` if the click wasn't used, in which case the mouse buttons work the
+    /// flippers.  `pos` is in screen pixels.
+    fn handle_mouse(
+        &mut self,
+        _pos: (i32, i32),
+        _button: MouseButton,
+        _state: ElementState,
+    ) -> bool {
+        false
+    }
+    /// Moves an analog plunger, from 0 at rest to 255 pulled all the way back.
+    fn handle_plunger(&mut self, _pos: u8) {}
+    /// Keeps the music in step with a game running `factor` frames for every frame shown.
+    fn set_turbo(&mut self, _factor: u8) {}
     fn render(&self, data: &mut [u8], pal: &mut [(u8, u8, u8)]);
+    /// Draws on top of the final RGBA frame, which is `width` pixels wide, like the view.
+    fn render_overlay(&self, _frame: &mut [u8], _width: usize) {}
+    /// Hands over the ball recording, if one was started.
+    fn take_trace(&mut self) -> Option<Trace> {
+        None
+    }
+    /// Hands over the score breakdown of the last finished game, if there is a new one.
+    fn take_breakdown_report(&mut self) -> Option<String> {
+        None
+    }
+    /// The game state, for views that have one.
+    fn inspect(&self) -> Option<TableInfo> {
+        None
+    }
 }